@@ -36,6 +36,21 @@ struct PkgCacheFile : public pkgCacheFile {
 		);
 	}
 
+	// Return a package by name and architecture.
+	UniquePtr<PkgIterator> find_pkg_arch(str name, str arch) const {
+		return std::make_unique<PkgIterator>(this->unconst()->GetPkgCache()->FindPkg(
+			{name.begin(), name.length()}, {arch.begin(), arch.length()}
+		));
+	}
+
+	// Return the first package of the group with this name.
+	UniquePtr<PkgIterator> find_group(str name) const {
+		pkgCache* cache = this->unconst()->GetPkgCache();
+		pkgCache::GrpIterator grp = cache->FindGrp({name.begin(), name.length()});
+		if (grp.end()) { return std::make_unique<PkgIterator>(pkgCache::PkgIterator(*cache, 0)); }
+		return std::make_unique<PkgIterator>(grp.PackageList());
+	}
+
 	UniquePtr<PkgIterator> begin() const {
 		return std::make_unique<PkgIterator>(this->unconst()->GetPkgCache()->PkgBegin());
 	}
@@ -15,7 +15,11 @@
 void init_config() { pkgInitConfig(*_config); }
 /// Initialize the apt system.
 
-void init_system() { pkgInitSystem(*_config, _system); }
+void init_system() {
+	pkgInitSystem(*_config, _system);
+	// Architectures are cached by apt, refresh them in case they were changed.
+	APT::Configuration::getArchitectures(false);
+}
 
 struct ConfigTree {
 	const Configuration::Item* ptr;
@@ -9,6 +9,13 @@
 
 using ActionGroup = pkgDepCache::ActionGroup;
 
+/// The StateCache of every package, indexed by package ID.
+struct DepCacheSavepoint {
+	/// The DepCache the states were copied from.
+	const pkgDepCache* owner;
+	std::vector<pkgDepCache::StateCache> states;
+};
+
 struct PkgDepCache {
 	pkgDepCache* ptr;
 
@@ -193,6 +200,89 @@ struct PkgDepCache {
 		handle_errors();
 	}
 
+	/// Write the auto installed marks to Dir::State::extended_states.
+	void write_state_file() const {
+		bool ok = ptr->writeStateFile(NULL);
+		handle_errors();
+		if (!ok) { throw std::runtime_error("Unable to write the extended states file"); }
+	}
+
+	/// Copy the state of every package so it can be restored later.
+	UniquePtr<DepCacheSavepoint> savepoint() const {
+		pkgCache& cache = ptr->GetCache();
+		auto save = std::make_unique<DepCacheSavepoint>();
+		save->owner = ptr;
+
+		// The packages aren't iterated in the order of their IDs.
+		save->states.resize(cache.Head().PackageCount);
+		for (pkgCache::PkgIterator pkg = cache.PkgBegin(); !pkg.end(); pkg++) {
+			save->states[pkg->ID] = (*ptr)[pkg];
+		}
+		return save;
+	}
+
+	/// Put every package back to the state it was in when the savepoint was made.
+	void restore(const DepCacheSavepoint& save) const {
+		pkgCache& cache = ptr->GetCache();
+		if (save.owner != ptr) {
+			throw std::runtime_error("Savepoint does not belong to this cache");
+		}
+
+		for (pkgCache::PkgIterator pkg = cache.PkgBegin(); !pkg.end(); pkg++) {
+			(*ptr)[pkg] = save.states[pkg->ID];
+		}
+
+		// Recalculate the dependency states and the counters from the restored modes.
+		ptr->Update();
+
+		// Update reads the extended states which may set the Auto flag again.
+		for (pkgCache::PkgIterator pkg = cache.PkgBegin(); !pkg.end(); pkg++) {
+			(*ptr)[pkg].Flags = save.states[pkg->ID].Flags;
+		}
+		handle_errors();
+	}
+
+	/// Keep the packages that changed since the savepoint.
+	///
+	/// Only the changed packages and their dependencies are updated, instead of the whole cache.
+	/// Returns false if one of them wasn't kept in the savepoint, restore is needed then.
+	bool restore_kept(const DepCacheSavepoint& save) const {
+		pkgCache& cache = ptr->GetCache();
+		if (save.owner != ptr) {
+			throw std::runtime_error("Savepoint does not belong to this cache");
+		}
+
+		std::vector<pkgCache::PkgIterator> changed;
+		for (pkgCache::PkgIterator pkg = cache.PkgBegin(); !pkg.end(); pkg++) {
+			const pkgDepCache::StateCache& now = (*ptr)[pkg];
+			const pkgDepCache::StateCache& then = save.states[pkg->ID];
+			if (now.Mode == then.Mode && now.InstallVer == then.InstallVer &&
+				now.CandidateVer == then.CandidateVer && now.iFlags == then.iFlags) {
+				continue;
+			}
+			if (then.Mode != pkgDepCache::ModeKeep) { return false; }
+			changed.push_back(pkg);
+		}
+
+		for (pkgCache::PkgIterator& pkg : changed) {
+			const pkgDepCache::StateCache& then = save.states[pkg->ID];
+
+			// A protected package can't be marked again.
+			(*ptr)[pkg].iFlags &= ~pkgDepCache::Protected;
+			ptr->MarkKeep(pkg, false, false);
+
+			if ((*ptr)[pkg].CandidateVer != then.CandidateVer) {
+				if (then.CandidateVer == nullptr) { return false; }
+				ptr->SetCandidateVersion(pkgCache::VerIterator(cache, then.CandidateVer));
+			}
+
+			(*ptr)[pkg].iFlags = then.iFlags;
+			(*ptr)[pkg].Flags = then.Flags;
+		}
+		handle_errors();
+		return true;
+	}
+
 	/// Clear any marked changes in the DepCache.
 	void init(OperationProgress& callback) const {
 		OpProgressWrapper op_progress(callback);
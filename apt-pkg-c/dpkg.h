@@ -0,0 +1,41 @@
+#pragma once
+#include <apt-pkg/cachefile.h>
+#include <apt-pkg/statechanges.h>
+#include <memory>
+#include "rust/cxx.h"
+
+#include "package.h"
+#include "types.h"
+#include "util.h"
+
+/// Selection changes that are written with `dpkg --set-selections`.
+struct StateChanges {
+	APT::StateChanges mutable changes;
+
+	void hold(const VerIterator& ver) const { changes.Hold(ver); }
+	void unhold(const VerIterator& ver) const { changes.Unhold(ver); }
+	void install(const VerIterator& ver) const { changes.Install(ver); }
+	void remove(const VerIterator& ver) const { changes.Remove(ver); }
+	void purge(const VerIterator& ver) const { changes.Purge(ver); }
+
+	bool empty() const { return changes.empty(); }
+	void clear() const { changes.clear(); }
+
+	/// Run dpkg to save the changes. The changes are cleared on success.
+	void save() const {
+		if (changes.Save()) {
+			changes.clear();
+			return;
+		}
+		handle_errors();
+
+		std::string failed;
+		for (auto const& ver : changes.Error()) {
+			if (!failed.empty()) { failed += ", "; }
+			failed += ver.ParentPkg().FullName(true);
+		}
+		throw std::runtime_error("Unable to set the selection of: " + failed);
+	}
+};
+
+inline UniquePtr<StateChanges> create_state_changes() { return std::make_unique<StateChanges>(); }
@@ -0,0 +1,58 @@
+#pragma once
+#include <apt-pkg/edsp.h>
+#include <apt-pkg/fileutl.h>
+#include <list>
+#include <memory>
+#include <unistd.h>
+#include "rust/cxx.h"
+
+#include "depcache.h"
+#include "progress.h"
+#include "types.h"
+#include "util.h"
+
+/// Write an EDSP request followed by the scenario to a file descriptor.
+///
+/// The file descriptor is owned and closed by this function.
+inline void write_request(const PkgDepCache& cache, int fd, u32 flags, OperationProgress& callback) {
+	OpProgressWrapper op_progress(callback);
+	FileFd output;
+	if (!output.OpenDescriptor(fd, FileFd::WriteOnly | FileFd::BufferedWrite, true)) {
+		handle_errors();
+	}
+
+	bool okay = !output.Failed();
+	if (okay) { okay = EDSP::WriteRequest(*cache.ptr, output, flags, &op_progress); }
+	if (okay) { okay = EDSP::WriteScenario(*cache.ptr, output, &op_progress); }
+	output.Close();
+
+	handle_errors();
+	if (!okay) { throw std::runtime_error("Unable to write the EDSP request"); }
+}
+
+/// Read an EDSP answer from a file descriptor and mark it on the depcache.
+///
+/// The file descriptor is owned and closed by this function.
+inline void read_response(const PkgDepCache& cache, int fd, OperationProgress& callback) {
+	OpProgressWrapper op_progress(callback);
+	bool okay = EDSP::ReadResponse(fd, *cache.ptr, &op_progress);
+
+	handle_errors();
+	if (!okay) { throw std::runtime_error("Unable to read the EDSP answer"); }
+}
+
+/// Read the request at the start of a scenario and mark it on the depcache.
+///
+/// The file descriptor is owned and closed by this function.
+inline void read_request(const PkgDepCache& cache, int fd) {
+	std::list<std::string> install, remove;
+	unsigned int flags;
+
+	bool okay = EDSP::ReadRequest(fd, install, remove, flags);
+	// ReadRequest reads the raw descriptor and leaves it open.
+	close(fd);
+	if (okay) { okay = EDSP::ApplyRequest(install, remove, *cache.ptr); }
+
+	handle_errors();
+	if (!okay) { throw std::runtime_error("Unable to read the EDSP request"); }
+}
@@ -79,6 +79,9 @@ struct VerFileIterator : public pkgCache::VerFileIterator {
 struct DescIterator : public pkgCache::DescIterator {
 	void raw_next() { (*this)++; }
 
+	str language_code() const { return this->LanguageCode(); }
+	str md5() const { return handle_str(pkgCache::DescIterator::md5()); }
+
 	UniquePtr<DescIterator> unique() const { return std::make_unique<DescIterator>(*this); }
 
 	DescIterator(const pkgCache::DescIterator& base) : pkgCache::DescIterator(base){};
@@ -107,6 +110,10 @@ struct VerIterator : public pkgCache::VerIterator {
 		return std::make_unique<DescIterator>(this->TranslatedDescription());
 	}
 
+	UniquePtr<DescIterator> description_list() const {
+		return std::make_unique<DescIterator>(this->DescriptionList());
+	}
+
 	// This is for backend records lookups.
 	// You go through here to get the package files.
 	UniquePtr<VerFileIterator> version_files() const {
@@ -155,6 +162,11 @@ struct PkgIterator : public pkgCache::PkgIterator {
 		return std::make_unique<DepIterator>(this->RevDependsList());
 	}
 
+	/// The next package in the group, which is the same name for another architecture.
+	UniquePtr<PkgIterator> next_in_group() const {
+		return std::make_unique<PkgIterator>(this->Group().NextPkg(*this));
+	}
+
 	UniquePtr<PkgIterator> unique() const { return std::make_unique<PkgIterator>(*this); }
 
 	PkgIterator(const pkgCache::PkgIterator& base) : pkgCache::PkgIterator(base){};
@@ -70,6 +70,9 @@ struct ProblemResolver {
 	/// modifying packages during resolution.
 	void protect(const PkgIterator& pkg) const { resolver.Protect(pkg); }
 
+	/// Tell the resolver that the package is to be removed. A protected package stays removed.
+	void remove(const PkgIterator& pkg) const { resolver.Remove(pkg); }
+
 	/// Try to resolve dependency problems by marking packages for installation and removal.
 	void resolve(bool fix_broken, OperationProgress& callback) const {
 		OpProgressWrapper op_progress(callback);
@@ -77,6 +80,13 @@ struct ProblemResolver {
 		handle_errors();
 	}
 
+	/// Try to resolve dependency problems only by keeping packages back.
+	void resolve_by_keep(OperationProgress& callback) const {
+		OpProgressWrapper op_progress(callback);
+		resolver.ResolveByKeep(&op_progress);
+		handle_errors();
+	}
+
 	ProblemResolver(pkgDepCache* depcache) : resolver(depcache){};
 };
 
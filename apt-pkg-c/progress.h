@@ -40,5 +40,14 @@ struct PackageManagerWrapper : public APT::Progress::PackageManagerFancy {
 		callback.error(pkgname, steps_done, total_steps, error);
 	};
 
+	void ConffilePrompt(
+		std::string pkgname,
+		unsigned int steps_done,
+		unsigned int total_steps,
+		std::string message
+	) {
+		callback.conffile_prompt(pkgname, steps_done, total_steps, message);
+	};
+
 	PackageManagerWrapper(InstallProgressFancy& callback) : callback(callback){};
 };
@@ -1,8 +1,11 @@
 #pragma once
 #include <apt-pkg/algorithms.h>
 #include <apt-pkg/cachefile.h>
+#include <apt-pkg/fileutl.h>
+#include <apt-pkg/hashes.h>
 #include <apt-pkg/install-progress.h>
 #include <apt-pkg/pkgsystem.h>
+#include <apt-pkg/strutl.h>
 #include <apt-pkg/version.h>
 #include <cstdint>
 #include <sstream>
@@ -84,3 +87,30 @@ inline void apt_unlock_inner() {
 
 /// Check if the lockfile is locked.
 inline bool apt_is_locked() { return _system->IsLocked(); }
+
+/// Hash a file. hash_type is the name used in Release files. ex: MD5Sum, SHA256
+inline String hash_file(str path, str hash_type) {
+	FileFd fd;
+	if (!fd.Open(std::string(path), FileFd::ReadOnly)) { handle_errors(); }
+
+	Hashes hashes;
+	if (!hashes.AddFD(fd)) { handle_errors(); }
+
+	const HashString* hash = hashes.GetHashStringList().find(std::string(hash_type));
+	if (hash == NULL) { throw std::runtime_error("Unknown hash type: " + std::string(hash_type)); }
+	return hash->HashValue();
+}
+
+/// Decompress a file with any compressor apt knows, picked by its extension.
+inline void decompress_file(str from, str to) {
+	FileFd input;
+	FileFd output;
+	if (!input.Open(std::string(from), FileFd::ReadOnly, FileFd::Extension) ||
+		!output.Open(std::string(to), FileFd::WriteOnly | FileFd::Create | FileFd::Empty) ||
+		!CopyFile(input, output) || !output.Close()) {
+		handle_errors();
+	}
+}
+
+/// Format a unix time like the Date field of a Release file.
+inline String time_rfc1123(i64 time) { return TimeRFC1123(time, false); }
@@ -9,6 +9,8 @@ fn main() {
 		"src/pkgmanager.rs",
 		"src/error.rs",
 		"src/acquire.rs",
+		"src/dpkg.rs",
+		"src/edsp.rs",
 		"src/iterators/package.rs",
 		"src/iterators/version.rs",
 		"src/iterators/dependency.rs",
@@ -40,6 +42,8 @@ fn main() {
 		"apt-pkg-c/error.h",
 		"apt-pkg-c/types.h",
 		"apt-pkg-c/acquire.h",
+		"apt-pkg-c/dpkg.h",
+		"apt-pkg-c/edsp.h",
 	]);
 
 	for file in cc_files {
@@ -1,23 +1,34 @@
 //! Contains Cache related structs.
 
 use std::cell::OnceCell;
+use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 use cxx::{Exception, UniquePtr};
 
-use crate::config::{init_config_system, Config};
+use crate::check::{check_installable, InstallCheck, Uninstallable};
+use crate::config::{init_config_system, Config, ConfigScope};
 use crate::depcache::DepCache;
+#[cfg(feature = "deb")]
+use crate::dpkg::ConffileAnswer;
+use crate::dpkg::{self, Selection};
 use crate::error::{pending_error, AptErrors};
 use crate::pkgmanager::raw::OrderResult;
+#[cfg(feature = "deb")]
+use crate::progress::ConffilePrompt;
 use crate::progress::{AcquireProgress, InstallProgress, OperationProgress};
 use crate::raw::{
 	create_cache, create_pkgmanager, create_problem_resolver, IntoRawIter, IterPkgIterator,
 	PackageManager, PkgCacheFile, PkgIterator, ProblemResolver,
 };
 use crate::records::{PackageRecords, SourceRecords};
-use crate::util::{apt_lock, apt_unlock, apt_unlock_inner};
-use crate::Package;
+use crate::search::{search_cache, SearchQuery, SearchResult};
+use crate::util::{
+	apt_lock, apt_unlock, apt_unlock_inner, decompress_file, BrokenReport, DiskSpace, TempDir,
+};
+use crate::{edsp, Marked, Package, PkgSelectedState};
 
 /// Selection of Upgrade type
 #[repr(i32)]
@@ -38,6 +49,78 @@ pub enum Upgrade {
 	SafeUpgrade = 3,
 }
 
+/// Why an upgradable package was not upgraded.
+#[derive(Debug)]
+pub enum HeldBackReason {
+	/// The package is on hold in dpkg.
+	Hold,
+	/// Upgrading would install these new packages,
+	/// which this type of upgrade does not allow.
+	NewPackages(Vec<String>),
+	/// Upgrading would remove these packages,
+	/// which this type of upgrade does not allow.
+	Removals(Vec<String>),
+	/// The dependencies of the new version can not be satisfied.
+	///
+	/// [`None`] if the package is not considered broken.
+	Broken(Option<BrokenReport>),
+	/// The resolver chose to keep it, for example because of phasing.
+	KeptBack,
+}
+
+/// An upgradable package that will not be upgraded.
+#[derive(Debug)]
+pub struct HeldBack<'a> {
+	pub package: Package<'a>,
+	pub reason: HeldBackReason,
+}
+
+/// The result of planning an upgrade with [`Cache::plan_upgrade`].
+#[derive(Debug)]
+pub struct UpgradeSummary<'a> {
+	/// Packages that will be newly installed.
+	pub install: u32,
+	/// Packages that will be upgraded.
+	pub upgrade: u32,
+	/// Packages that will be downgraded.
+	pub downgrade: u32,
+	/// Packages that will be removed.
+	pub remove: u32,
+	/// Upgradable packages that will be kept at their current version.
+	///
+	/// This is the "not upgraded" count from apt.
+	pub kept: u32,
+	/// Each kept package along with the reason it was kept.
+	pub held_back: Vec<HeldBack<'a>>,
+	/// The amount of bytes that need to be downloaded.
+	pub download_size: u64,
+	/// The disk space that will be used or freed.
+	pub disk_size: DiskSpace,
+}
+
+/// What the problem resolver changed compared to what was requested.
+#[derive(Debug, Default)]
+pub struct ResolveResult<'a> {
+	/// Packages that were marked for install but are now kept.
+	pub kept_back: Vec<Package<'a>>,
+	/// Packages that were not marked for removal but now are.
+	pub removed: Vec<Package<'a>>,
+	/// Packages that were not marked for install but now are.
+	pub installed: Vec<Package<'a>>,
+	/// Packages that were marked for removal but are now kept.
+	pub not_removed: Vec<Package<'a>>,
+}
+
+impl ResolveResult<'_> {
+	/// True if the resolver didn't change anything that was requested.
+	pub fn is_empty(&self) -> bool {
+		self.kept_back.is_empty()
+			&& self.removed.is_empty()
+			&& self.installed.is_empty()
+			&& self.not_removed.is_empty()
+	}
+}
+
 /// Selection of how to sort
 enum Sort {
 	/// Disable the sort method.
@@ -140,6 +223,240 @@ impl PackageSort {
 	}
 }
 
+/// Open a [`Cache`] with its own configuration.
+///
+/// The values are only set while the cache is opened, so other code in the
+/// process keeps using the normal configuration. This lets you read a chroot,
+/// a container image or a sysroot without touching the host.
+///
+/// Paths are read inside of the root dir if one is set.
+/// Without one, relative paths are from the current directory.
+///
+/// Operations that are run later, like [`Cache::commit`] or
+/// [`Cache::update`], use the configuration at the time they are run.
+///
+/// # Example:
+/// ```
+/// use oma_apt::cache::CacheBuilder;
+///
+/// let cache = CacheBuilder::new()
+///     .status("/dev/null")
+///     .source_list("/dev/null")
+///     .source_parts("/dev/null")
+///     .local_files(&["tests/files/cache/Packages"])
+///     .build()
+///     .unwrap();
+///
+/// assert!(cache.get("apt").is_some());
+/// assert!(!cache.get("apt").unwrap().is_installed());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct CacheBuilder {
+	root_dir: Option<String>,
+	status: Option<String>,
+	lists: Option<String>,
+	source_list: Option<String>,
+	source_parts: Option<String>,
+	architectures: Vec<String>,
+	config: Vec<(String, String)>,
+	local_files: Vec<String>,
+	isolated: bool,
+}
+
+impl CacheBuilder {
+	pub fn new() -> Self { Self::default() }
+
+	/// Read everything relative to this directory, like `-o RootDir=`.
+	pub fn root_dir<P: AsRef<Path>>(mut self, path: P) -> Self {
+		let path = std::path::absolute(path.as_ref()).unwrap_or(path.as_ref().to_path_buf());
+		self.root_dir = Some(path.to_string_lossy().to_string());
+		self
+	}
+
+	/// The dpkg status file. Use `/dev/null` for nothing installed.
+	pub fn status<P: AsRef<Path>>(mut self, path: P) -> Self {
+		self.status = Some(path.as_ref().to_string_lossy().to_string());
+		self
+	}
+
+	/// The directory with the downloaded package lists.
+	pub fn lists<P: AsRef<Path>>(mut self, path: P) -> Self {
+		self.lists = Some(path.as_ref().to_string_lossy().to_string());
+		self
+	}
+
+	/// The main sources file, normally `/etc/apt/sources.list`.
+	pub fn source_list<P: AsRef<Path>>(mut self, path: P) -> Self {
+		self.source_list = Some(path.as_ref().to_string_lossy().to_string());
+		self
+	}
+
+	/// The directory of extra sources, normally `/etc/apt/sources.list.d`.
+	pub fn source_parts<P: AsRef<Path>>(mut self, path: P) -> Self {
+		self.source_parts = Some(path.as_ref().to_string_lossy().to_string());
+		self
+	}
+
+	/// The architectures to use. The first one is the native architecture.
+	pub fn architectures<T: AsRef<str>>(mut self, archs: &[T]) -> Self {
+		self.architectures = archs.iter().map(|a| a.as_ref().to_string()).collect();
+		self
+	}
+
+	/// Set any other configuration value while the cache is opened.
+	pub fn config(mut self, key: &str, value: &str) -> Self {
+		self.config.push((key.to_string(), value.to_string()));
+		self
+	}
+
+	/// Only read the local files and the status file.
+	///
+	/// Nothing is read from the sources, the package lists, the extended
+	/// states or the preferences of the system. Nothing is installed unless a
+	/// [`CacheBuilder::status`] file is given.
+	///
+	/// This is useful to check a snapshot of a repository offline.
+	///
+	/// # Example:
+	/// ```
+	/// use oma_apt::cache::CacheBuilder;
+	///
+	/// let cache = CacheBuilder::new()
+	///     .isolated()
+	///     .local_files(&["tests/files/cache/Packages"])
+	///     .build()
+	///     .unwrap();
+	///
+	/// assert!(cache.get("dep-pkg1").is_some());
+	/// assert!(cache.get("dpkg").is_none());
+	/// ```
+	pub fn isolated(mut self) -> Self {
+		self.isolated = true;
+		self
+	}
+
+	/// Files to add to the cache. See [`Cache::new`] for what is allowed.
+	pub fn local_files<T: AsRef<str>>(mut self, files: &[T]) -> Self {
+		self.local_files
+			.extend(files.iter().map(|f| f.as_ref().to_string()));
+		self
+	}
+
+	/// Open the cache.
+	pub fn build(&self) -> Result<Cache, AptErrors> {
+		let cache = self.open();
+		// Put the system back the way it was for everyone else.
+		init_config_system();
+		cache
+	}
+
+	/// apt finds relative paths from the parent directory in the config,
+	/// so they are made absolute unless there is a root dir.
+	fn config_path(&self, path: &str) -> String {
+		if self.root_dir.is_some() || Path::new(path).is_absolute() {
+			return path.to_string();
+		}
+		std::path::absolute(path)
+			.map(|path| path.to_string_lossy().to_string())
+			.unwrap_or_else(|_| path.to_string())
+	}
+
+	fn open(&self) -> Result<Cache, AptErrors> {
+		let mut scope = ConfigScope::new();
+
+		// Never replace the binary caches of the host with ours.
+		scope.set("Dir::Cache::pkgcache", "");
+		scope.set("Dir::Cache::srcpkgcache", "");
+
+		if self.isolated {
+			for key in [
+				"Dir::Etc::sourcelist",
+				"Dir::Etc::sourceparts",
+				"Dir::Etc::preferences",
+				"Dir::Etc::preferencesparts",
+				"Dir::State::status",
+				"Dir::State::extended_states",
+			] {
+				scope.set(key, "/dev/null");
+			}
+		}
+
+		let dirs = [
+			("RootDir", &self.root_dir),
+			("Dir::State::status", &self.status),
+			("Dir::State::lists", &self.lists),
+			("Dir::Etc::sourcelist", &self.source_list),
+			("Dir::Etc::sourceparts", &self.source_parts),
+		];
+		for (key, value) in dirs {
+			if let Some(value) = value {
+				scope.set(key, &self.config_path(value));
+			}
+		}
+
+		if let Some(native) = self.architectures.first() {
+			scope.set("APT::Architecture", native);
+			scope.set_vector(
+				"APT::Architectures",
+				&self.architectures.iter().map(|a| a.as_str()).collect(),
+			);
+		}
+
+		for (key, value) in &self.config {
+			scope.set(key, value);
+		}
+
+		// The system reads the status file and the admin dir from the config.
+		init_config_system();
+		let cache = Cache::open(&self.local_files)?;
+		// The policy and extended states are read with the depcache,
+		// so it has to be made while the scope is still active.
+		cache.depcache();
+		Ok(cache)
+	}
+}
+
+/// apt only reads local `Packages` and `Sources` files by their exact name,
+/// so compressed ones are decompressed into a temporary directory first.
+///
+/// The paths in `files` are replaced with the decompressed copies.
+fn decompress_indexes(files: &mut [String]) -> Result<Option<TempDir>, AptErrors> {
+	let mut dir: Option<TempDir> = None;
+
+	for (i, file) in files.iter_mut().enumerate() {
+		let path = Path::new(file.as_str());
+		let (Some(name), Some(ext)) = (path.file_stem(), path.extension()) else {
+			continue;
+		};
+		if !["Packages", "Sources"].iter().any(|index| name == *index)
+			|| !["gz", "xz", "zst", "bz2", "lzma", "lz4"]
+				.iter()
+				.any(|comp| ext == *comp)
+		{
+			continue;
+		}
+
+		let dir = match &mut dir {
+			Some(dir) => dir,
+			None => dir.insert(TempDir::new("oma-apt-indexes")?),
+		};
+		// Each file gets its own directory, as they all have the same name.
+		let target = dir.path().join(i.to_string());
+		fs::create_dir(&target)?;
+		let target = target.join(name);
+
+		decompress_file(path, &target)?;
+		*file = target.to_string_lossy().to_string();
+	}
+
+	Ok(dir)
+}
+
+/// The native architecture in the config.
+fn config_native_arch() -> String {
+	crate::raw::config::find("APT::Architecture".to_string(), "".to_string())
+}
+
 /// The main struct for accessing any and all `apt` data.
 pub struct Cache {
 	pub(crate) ptr: UniquePtr<PkgCacheFile>,
@@ -149,6 +466,15 @@ pub struct Cache {
 	pkgmanager: OnceCell<UniquePtr<PackageManager>>,
 	problem_resolver: OnceCell<UniquePtr<ProblemResolver>>,
 	local_debs: Vec<String>,
+	/// Decompressed copies of the local files, which apt reads as long as
+	/// the cache is open.
+	_local_indexes: Option<TempDir>,
+	/// `RootDir` when the cache was opened.
+	root_dir: PathBuf,
+	/// The dpkg admin dir when the cache was opened.
+	admin_dir: PathBuf,
+	/// `APT::Architecture` when the cache was opened.
+	native_arch: String,
 }
 
 impl Cache {
@@ -160,7 +486,8 @@ impl Cache {
 	///
 	/// - `*.deb` or `*.ddeb` files
 	/// - `Packages` and `Sources` files from apt repositories. These files can
-	///   be compressed.
+	///   be compressed with any compressor apt knows, like `.gz`, `.xz` or
+	///   `.zst`.
 	/// - `*.dsc` or `*.changes` files
 	/// - A valid directory containing the file `./debian/control`
 	///
@@ -170,9 +497,88 @@ impl Cache {
 	/// Note that if you run [`Cache::commit`] or [`Cache::update`],
 	/// You will be required to make a new cache to perform any further changes
 	pub fn new<T: AsRef<str>>(local_files: &[T]) -> Result<Cache, AptErrors> {
-		let volatile_files: Vec<_> = local_files.iter().map(|d| d.as_ref()).collect();
+		init_config_system();
+		Self::open(local_files)
+	}
+
+	/// Open a cache from a scenario written by [`Cache::dump_scenario`].
+	///
+	/// Nothing is read from the system, so the cache will be the same on any
+	/// machine. The installed packages, candidates and marks all come from the
+	/// scenario.
+	///
+	/// The configuration and the apt system are only switched to the scenario
+	/// while it is opened, like with [`CacheBuilder`], so other caches opened
+	/// later read the system as usual.
+	///
+	/// # Example:
+	/// ```
+	/// use oma_apt::new_cache;
+	/// use oma_apt::cache::Cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// cache.get("apt").unwrap().mark_reinstall(true);
+	///
+	/// let path = std::env::temp_dir().join("oma-apt-doc-scenario");
+	/// cache.dump_scenario(&path).unwrap();
+	///
+	/// let scenario = Cache::from_scenario(&path).unwrap();
+	/// assert!(scenario.get("apt").unwrap().is_installed());
+	/// ```
+	pub fn from_scenario<P: AsRef<Path>>(path: P) -> Result<Cache, AptErrors> {
+		let path = fs::canonicalize(path.as_ref()).map_err(|err| {
+			AptErrors::from(format!(
+				"Unable to open the scenario '{}': {err}",
+				path.as_ref().display()
+			))
+		})?;
+
+		let cache = Self::open_scenario(&path);
+		// Put the system back the way it was for everyone else.
+		init_config_system();
+		cache
+	}
 
+	fn open_scenario(path: &Path) -> Result<Cache, AptErrors> {
+		let mut scope = ConfigScope::new();
+		scope.set("APT::System", "Debian APT solver interface");
+		scope.set("edsp::scenario", &path.to_string_lossy());
+		scope.set("Dir::Etc::sourcelist", "/dev/null");
+		scope.set("Dir::Etc::sourceparts", "/dev/null");
+		scope.set("Dir::Cache::pkgcache", "");
+		scope.set("Dir::Cache::srcpkgcache", "");
+
+		// The EDSP system sets these when it is initialized.
+		for key in [
+			"Dir::Log",
+			"Dir::Etc::preferences",
+			"Dir::Etc::preferencesparts",
+			"Dir::State::status",
+			"Dir::State::lists",
+			"Dir::State::extended_states",
+			"Debug::NoLocking",
+			"APT::Get::Simulate",
+		] {
+			scope.save(key);
+		}
 		init_config_system();
+
+		let files: [&str; 0] = [];
+		let mut cache = Self::open(&files)?;
+		// The extended states and preferences of the scenario are read with the
+		// depcache, so it has to be made while the scope is still active.
+		cache.depcache();
+		edsp::read_request(&cache, path)?;
+		// The architectures are set from the request.
+		cache.native_arch = config_native_arch();
+		Ok(cache)
+	}
+
+	fn open<T: AsRef<str>>(local_files: &[T]) -> Result<Cache, AptErrors> {
+		let mut files: Vec<_> = local_files.iter().map(|d| d.as_ref().to_string()).collect();
+		let local_indexes = decompress_indexes(&mut files)?;
+		let volatile_files: Vec<_> = files.iter().map(|f| f.as_str()).collect();
+
 		Ok(Cache {
 			ptr: create_cache(&volatile_files)?,
 			depcache: OnceCell::new(),
@@ -185,6 +591,10 @@ impl Cache {
 				.filter(|f| f.ends_with(".deb"))
 				.map(|f| f.to_string())
 				.collect(),
+			_local_indexes: local_indexes,
+			root_dir: dpkg::config_root_dir(),
+			admin_dir: dpkg::config_admin_dir(),
+			native_arch: config_native_arch(),
 		})
 	}
 
@@ -398,6 +808,141 @@ impl Cache {
 			.upgrade(progress.pin().as_mut(), upgrade_type as i32)?)
 	}
 
+	/// Mark all packages for upgrade and summarize what would happen.
+	///
+	/// This counts the changes like `apt upgrade` shows before asking to
+	/// continue, and explains why each upgradable package was held back.
+	///
+	/// If `dry_run` is [`true`] the marks are restored afterwards,
+	/// otherwise they are left in place for [`Cache::commit`].
+	///
+	/// # Example:
+	/// ```
+	/// use oma_apt::cache::Upgrade;
+	/// use oma_apt::new_cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// let summary = cache.plan_upgrade(Upgrade::Upgrade, true).unwrap();
+	///
+	/// println!(
+	///     "{} upgraded, {} newly installed, {} to remove and {} not upgraded.",
+	///     summary.upgrade, summary.install, summary.remove, summary.kept
+	/// );
+	///
+	/// for held in summary.held_back {
+	///     println!("{} was held back: {:?}", held.package.name(), held.reason);
+	/// }
+	/// ```
+	pub fn plan_upgrade(
+		&self,
+		upgrade_type: Upgrade,
+		dry_run: bool,
+	) -> Result<UpgradeSummary, AptErrors> {
+		let depcache = self.depcache();
+		let save = depcache.savepoint();
+
+		if let Err(err) = self.upgrade(upgrade_type) {
+			if dry_run {
+				depcache.restore(&save)?;
+			}
+			return Err(err);
+		}
+
+		let mut summary = UpgradeSummary {
+			install: 0,
+			upgrade: 0,
+			downgrade: 0,
+			remove: 0,
+			kept: depcache.keep_count(),
+			held_back: vec![],
+			download_size: depcache.download_size(),
+			disk_size: depcache.disk_size(),
+		};
+
+		for pkg in self.get_changes(false) {
+			match pkg.marked() {
+				Marked::NewInstall => summary.install += 1,
+				Marked::Upgrade => summary.upgrade += 1,
+				Marked::Downgrade => summary.downgrade += 1,
+				Marked::Remove | Marked::Purge => summary.remove += 1,
+				_ => {},
+			}
+		}
+
+		let held_back = self.held_back();
+		// The upgrade is undone even if a trial failed.
+		if dry_run {
+			depcache.restore(&save)?;
+		}
+		summary.held_back = held_back?;
+		Ok(summary)
+	}
+
+	/// Find the upgradable packages that the upgrade kept, and why.
+	fn held_back(&self) -> Result<Vec<HeldBack<'_>>, AptErrors> {
+		let depcache = self.depcache();
+		// Each held back package is tried on top of the upgrade.
+		let upgraded = depcache.savepoint();
+		let before: Vec<u64> = self.get_changes(false).map(|p| p.index()).collect();
+		let group = depcache.action_guard();
+
+		let mut held_back = vec![];
+		let upgradable = PackageSort::default().upgradable();
+		for pkg in self.packages(&upgradable) {
+			if !pkg.marked_keep() {
+				continue;
+			}
+			let reason = self.held_back_reason(&pkg, &before);
+			// Only the packages that the trial changed are marked again.
+			if !depcache.restore_kept(&upgraded)? {
+				depcache.restore(&upgraded)?;
+			}
+			held_back.push(HeldBack {
+				package: pkg,
+				reason,
+			});
+		}
+		group.release();
+		Ok(held_back)
+	}
+
+	/// Try to upgrade a single package to find out why it was held back.
+	///
+	/// `before` are the packages that were already changed. The trial is left
+	/// marked for the caller to undo.
+	fn held_back_reason(&self, pkg: &Package, before: &[u64]) -> HeldBackReason {
+		if pkg.selected_state() == PkgSelectedState::Hold {
+			return HeldBackReason::Hold;
+		}
+
+		pkg.mark_install(true, false);
+
+		if !pkg.marked_install() || pkg.is_inst_broken() {
+			HeldBackReason::Broken(BrokenReport::new(self, pkg, false))
+		} else {
+			let mut new_pkgs = vec![];
+			let mut removals = vec![];
+			for change in self.get_changes(true) {
+				if before.contains(&change.index()) || change.index() == pkg.index() {
+					continue;
+				}
+				if change.marked_delete() {
+					removals.push(change.fullname(true));
+				} else if change.marked_new_install() {
+					new_pkgs.push(change.fullname(true));
+				}
+			}
+
+			if !removals.is_empty() {
+				HeldBackReason::Removals(removals)
+			} else if !new_pkgs.is_empty() {
+				HeldBackReason::NewPackages(new_pkgs)
+			} else {
+				HeldBackReason::KeptBack
+			}
+		}
+	}
+
 	/// Resolve dependencies with the changes marked on all packages. This marks
 	/// additional packages for installation/removal to satisfy the dependency
 	/// chain.
@@ -419,6 +964,136 @@ impl Cache {
 			.resolve(fix_broken, OperationProgress::quiet().pin().as_mut())?)
 	}
 
+	/// Same as [`Cache::resolve`], but with your own progress
+	/// and a report of what the resolver changed.
+	///
+	/// # Example:
+	/// ```
+	/// use oma_apt::new_cache;
+	/// use oma_apt::progress::OperationProgress;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// let mut progress = OperationProgress::quiet();
+	///
+	/// cache.get("apt").unwrap().mark_install(true, true);
+	/// let result = cache.resolve_with_progress(true, &mut progress).unwrap();
+	///
+	/// for pkg in result.removed {
+	///     println!("{} will be removed", pkg.name())
+	/// }
+	/// ```
+	pub fn resolve_with_progress(
+		&self,
+		fix_broken: bool,
+		progress: &mut OperationProgress,
+	) -> Result<ResolveResult, AptErrors> {
+		self.resolve_changes(|resolver| resolver.resolve(fix_broken, progress.pin()))
+	}
+
+	/// Resolve dependency problems only by keeping back packages
+	/// that are marked for changes, like `apt upgrade` does.
+	///
+	/// Nothing new is installed or removed by this.
+	pub fn resolve_by_keep(
+		&self,
+		progress: &mut OperationProgress,
+	) -> Result<ResolveResult, AptErrors> {
+		self.resolve_changes(|resolver| resolver.resolve_by_keep(progress.pin()))
+	}
+
+	/// Write the marks on the depcache and every package as an EDSP request.
+	///
+	/// See [`crate::edsp::RequestFlags`] for the flags.
+	pub fn write_edsp_request<P: AsRef<Path>>(&self, path: P, flags: u32) -> Result<(), AptErrors> {
+		edsp::write_request(self, path.as_ref(), flags, &mut OperationProgress::quiet())
+	}
+
+	/// Write everything needed to reproduce this cache to one file.
+	///
+	/// The scenario is an EDSP request. It has the marks on the depcache
+	/// followed by every version with its installed state, candidate and pin.
+	/// Open it again with [`Cache::from_scenario`].
+	pub fn dump_scenario<P: AsRef<Path>>(&self, path: P) -> Result<(), AptErrors> {
+		self.write_edsp_request(path, 0)
+	}
+
+	/// Mark an EDSP answer from a solver onto the depcache.
+	pub fn read_edsp_response<P: AsRef<Path>>(&self, path: P) -> Result<(), AptErrors> {
+		edsp::read_response(self, path.as_ref(), &mut OperationProgress::quiet())
+	}
+
+	/// Resolve dependencies with an external solver that speaks EDSP.
+	///
+	/// The request is written to the stdin of `solver` and the answer
+	/// is read from its stdout.
+	///
+	/// # Example:
+	/// ```no_run
+	/// use std::process::Command;
+	///
+	/// use oma_apt::edsp::RequestFlags;
+	/// use oma_apt::new_cache;
+	/// use oma_apt::progress::OperationProgress;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// let mut progress = OperationProgress::quiet();
+	///
+	/// cache.get("neovim").unwrap().mark_install(false, true);
+	/// cache
+	///     .resolve_external(
+	///         &mut Command::new("/usr/lib/apt/solvers/apt"),
+	///         RequestFlags::ForbidRemove,
+	///         &mut progress,
+	///     )
+	///     .unwrap();
+	/// ```
+	pub fn resolve_external(
+		&self,
+		solver: &mut Command,
+		flags: u32,
+		progress: &mut OperationProgress,
+	) -> Result<(), AptErrors> {
+		edsp::solve(self, solver, flags, progress)
+	}
+
+	/// Run the resolver and compare the marks afterwards
+	/// with the marks from before.
+	fn resolve_changes(
+		&self,
+		resolve: impl FnOnce(&ProblemResolver) -> Result<(), Exception>,
+	) -> Result<ResolveResult, AptErrors> {
+		let mut requested = HashSet::new();
+		let mut deleted = HashSet::new();
+		for pkg in self.get_changes(false) {
+			if pkg.marked_install() {
+				requested.insert(pkg.index());
+			}
+			if pkg.marked_delete() {
+				deleted.insert(pkg.index());
+			}
+		}
+
+		resolve(self.resolver())?;
+
+		let mut result = ResolveResult::default();
+		for pkg in self.packages(&PackageSort::default()) {
+			if requested.contains(&pkg.index()) {
+				if !pkg.marked_install() {
+					result.kept_back.push(pkg);
+				}
+			} else if deleted.contains(&pkg.index()) {
+				if !pkg.marked_delete() {
+					result.not_removed.push(pkg);
+				}
+			} else if pkg.marked_install() {
+				result.installed.push(pkg);
+			} else if pkg.marked_delete() {
+				result.removed.push(pkg);
+			}
+		}
+		Ok(result)
+	}
+
 	/// Autoinstall every broken package and run the problem resolver
 	/// Returns false if the problem resolver fails.
 	///
@@ -577,6 +1252,119 @@ impl Cache {
 		progress: &mut AcquireProgress,
 		install_progress: &mut InstallProgress,
 	) -> Result<(), AptErrors> {
+		self.fetch_archives(progress)?;
+
+		// Perform the operation.
+		self.do_install(install_progress)?;
+
+		// Finally Unlock the whole thing.
+		apt_unlock();
+		Ok(())
+	}
+
+	/// Like [`Cache::commit`], but conffile prompts are answered by `handler`.
+	///
+	/// See [`Cache::do_install_with_conffiles`].
+	#[cfg(feature = "deb")]
+	pub fn commit_with_conffiles(
+		self,
+		progress: &mut AcquireProgress,
+		install_progress: &mut InstallProgress,
+		handler: impl FnMut(&Package, &ConffilePrompt, Option<&str>) -> ConffileAnswer,
+	) -> Result<(), AptErrors> {
+		self.fetch_archives(progress)?;
+
+		self.do_install_with_conffiles(install_progress, handler)?;
+
+		apt_unlock();
+		Ok(())
+	}
+
+	/// Like [`Cache::do_install`], but dpkg doesn't stop to ask about
+	/// conffiles.
+	///
+	/// dpkg can only be answered on its terminal, so its `pmconffile` prompts
+	/// are worked out before it runs. `handler` is called for each conffile
+	/// that dpkg would ask about, with the package and the same
+	/// [`ConffilePrompt`] that dpkg would send. The new file of the prompt is
+	/// a temporary copy from the archive. [`ConffileAnswer::ShowDiff`] calls
+	/// `handler` again with the diff.
+	///
+	/// This covers upgrades, downgrades and reinstalls, new installs over a
+	/// file that is already on disk, and packages that only have their
+	/// conffiles left.
+	///
+	/// If every answer is the same, dpkg is run with `--force-confold` or
+	/// `--force-confnew` added to `DPkg::Options` for this install only.
+	/// If the answers are mixed, the conffiles answered with
+	/// [`ConffileAnswer::Replace`] are replaced before dpkg runs and the old
+	/// file is saved as `.dpkg-old`. dpkg then keeps the rest with
+	/// `--force-confold`. The replaced files are put back if the install
+	/// fails.
+	///
+	/// Anything that dpkg still asks about gets the same forced answer
+	/// without calling `handler`, and dpkg asks on the terminal if nothing was
+	/// answered. This happens for packages whose archive isn't downloaded, so
+	/// call [`Cache::get_archives`] first.
+	///
+	/// # Example:
+	/// ```no_run
+	/// use oma_apt::cache::Upgrade;
+	/// use oma_apt::dpkg::ConffileAnswer;
+	/// use oma_apt::new_cache;
+	/// use oma_apt::progress::{AcquireProgress, InstallProgress};
+	///
+	/// let cache = new_cache!().unwrap();
+	/// let mut acquire_progress = AcquireProgress::apt();
+	/// let mut install_progress = InstallProgress::apt();
+	///
+	/// cache.upgrade(Upgrade::FullUpgrade).unwrap();
+	///
+	/// // These need root
+	/// cache.get_archives(&mut acquire_progress).unwrap();
+	/// cache
+	///     .do_install_with_conffiles(&mut install_progress, |pkg, prompt, diff| {
+	///         match diff {
+	///             None => ConffileAnswer::ShowDiff,
+	///             Some(diff) => {
+	///                 println!("{}: {}\n{diff}", pkg.name(), prompt.current);
+	///                 ConffileAnswer::Keep
+	///             },
+	///         }
+	///     })
+	///     .unwrap();
+	/// ```
+	#[cfg(feature = "deb")]
+	pub fn do_install_with_conffiles(
+		self,
+		progress: &mut InstallProgress,
+		mut handler: impl FnMut(&Package, &ConffilePrompt, Option<&str>) -> ConffileAnswer,
+	) -> Result<(), AptErrors> {
+		let mut answers = dpkg::answer_conffiles(&self, &mut handler)?;
+		let Some(option) = answers.prepare()? else {
+			return self.do_install(progress);
+		};
+
+		let mut options = Config::new().find_vector("DPkg::Options");
+		options.push(option.to_string());
+
+		let mut scope = ConfigScope::new();
+		scope.set_vector(
+			"DPkg::Options",
+			&options.iter().map(|option| option.as_str()).collect(),
+		);
+		let result = self.do_install(progress);
+		drop(scope);
+
+		if result.is_err() {
+			answers.restore();
+		}
+		result
+	}
+
+	/// Lock, copy the local debs into the archives dir and download the
+	/// archives. The dpkg files are unlocked for the install after.
+	fn fetch_archives(&self, progress: &mut AcquireProgress) -> Result<(), AptErrors> {
 		// Lock the whole thing so as to prevent tamper
 		apt_lock()?;
 
@@ -598,12 +1386,6 @@ impl Cache {
 		// If the system is locked we will want to unlock the dpkg files.
 		// This way when dpkg is running it can access its files.
 		apt_unlock_inner();
-
-		// Perform the operation.
-		self.do_install(install_progress)?;
-
-		// Finally Unlock the whole thing.
-		apt_unlock();
 		Ok(())
 	}
 
@@ -618,6 +1400,136 @@ impl Cache {
 		}))
 	}
 
+	/// Get a single package for an architecture.
+	///
+	/// This follows the rules of apt:
+	/// * `native` and `all` are the native architecture.
+	/// * `any` is the preferred package, like [`Cache::get`] without an
+	///   architecture.
+	///
+	/// `cache.get_arch("libc6", "i386")` is the same as
+	/// `cache.get("libc6:i386")`.
+	pub fn get_arch(&self, name: &str, arch: &str) -> Option<Package> {
+		Some(Package::new(self, unsafe {
+			self.find_pkg_arch(name, arch).make_safe()?
+		}))
+	}
+
+	/// Get the package for every architecture that has this name.
+	///
+	/// This is empty if there isn't a package with the name.
+	///
+	/// # Example:
+	/// ```
+	/// use oma_apt::new_cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// for pkg in cache.group("libc6") {
+	///     println!("{}", pkg.fullname(false));
+	/// }
+	/// ```
+	pub fn group(&self, name: &str) -> Vec<Package> {
+		let mut group = vec![];
+		let mut next = unsafe { self.find_group(name).make_safe() };
+
+		while let Some(pkg) = next {
+			next = unsafe { pkg.next_in_group().make_safe() };
+			group.push(Package::new(self, pkg));
+		}
+		group
+	}
+
+	/// The native architecture that the cache was opened with. ex: `amd64`
+	///
+	/// This is the first of [`CacheBuilder::architectures`] if they were set.
+	pub fn native_arch(&self) -> &str { &self.native_arch }
+
+	/// The directory that the system was read from, like `-o RootDir=`.
+	///
+	/// This is `/` unless the cache was opened with a root dir.
+	pub fn root_dir(&self) -> &Path { &self.root_dir }
+
+	/// The dpkg admin dir that the cache was opened with, ex: `/var/lib/dpkg`
+	///
+	/// This is the directory of `Dir::State::status`, under the root dir.
+	pub fn admin_dir(&self) -> &Path { &self.admin_dir }
+
+	/// Find the packages that installed a path, like `dpkg -S`.
+	///
+	/// The path must be exact. ex: `/usr/bin/apt`
+	pub fn owner_of<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Package>, AptErrors> {
+		dpkg::owner_of(self, path.as_ref())
+	}
+
+	/// Get the selected state of every package like `dpkg --get-selections`.
+	///
+	/// Packages that have never had a selection are not included.
+	pub fn get_selections(&self) -> Vec<Selection> { dpkg::get_selections(self) }
+
+	/// Write many selections at once like `dpkg --set-selections`.
+	///
+	/// Nothing is written if a package can't be found.
+	/// Create a new cache afterwards to see the new selected states.
+	///
+	/// # Example:
+	/// ```no_run
+	/// use oma_apt::new_cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	///
+	/// // Save the selections somewhere
+	/// let selections = cache.get_selections();
+	///
+	/// // And restore them later
+	/// cache.set_selections(&selections).unwrap();
+	/// ```
+	pub fn set_selections(&self, selections: &[Selection]) -> Result<(), AptErrors> {
+		let mut pkgs = vec![];
+		for selection in selections {
+			let Some(pkg) = self.get(&selection.package) else {
+				return Err(AptErrors::from(format!(
+					"Unable to locate package {}",
+					selection.package
+				)));
+			};
+			pkgs.push((pkg, selection.state.clone()));
+		}
+		dpkg::set_selections(pkgs)
+	}
+
+	/// Search package names, provides and descriptions like `apt-cache
+	/// search`.
+	///
+	/// Every term in the query must match for a package to be included.
+	/// Results are ranked with the best match first.
+	///
+	/// This looks up the records of every package on each call. If you are
+	/// going to search more than once use a [`crate::search::SearchIndex`].
+	///
+	/// # Example:
+	/// ```
+	/// use oma_apt::new_cache;
+	/// use oma_apt::search::SearchQuery;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// let results = cache.search(&SearchQuery::new("^apt$")).unwrap();
+	///
+	/// assert_eq!(results[0].package.name(), "apt");
+	/// ```
+	///
+	/// Returns [`Err`] if one of the terms is not a valid regular expression.
+	pub fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>, AptErrors> {
+		search_cache(self, query)
+	}
+
+	/// Find the versions that can't be installed, like `dose-debcheck`.
+	///
+	/// The marks on the depcache are the same afterwards.
+	/// See [`crate::check`] for more information.
+	pub fn check_installable(&self, check: &InstallCheck) -> Result<Vec<Uninstallable>, AptErrors> {
+		check_installable(self, check)
+	}
+
 	/// An iterator over the packages
 	/// that will be altered when `cache.commit()` is called.
 	///
@@ -732,6 +1644,30 @@ pub(crate) mod raw {
 		/// The returned UniquePtr cannot outlive the cache.
 		unsafe fn find_pkg(self: &PkgCacheFile, name: &str) -> UniquePtr<PkgIterator>;
 
+		/// Return a package by name and architecture.
+		///
+		/// # Safety
+		///
+		/// If the Internal Pkg Pointer is NULL, operations can segfault.
+		/// You should call `make_safe()` asap to convert it to an Option.
+		///
+		/// The returned UniquePtr cannot outlive the cache.
+		unsafe fn find_pkg_arch(
+			self: &PkgCacheFile,
+			name: &str,
+			arch: &str,
+		) -> UniquePtr<PkgIterator>;
+
+		/// Return the first package of a group.
+		///
+		/// # Safety
+		///
+		/// If the Internal Pkg Pointer is NULL, operations can segfault.
+		/// You should call `make_safe()` asap to convert it to an Option.
+		///
+		/// The returned UniquePtr cannot outlive the cache.
+		unsafe fn find_group(self: &PkgCacheFile, name: &str) -> UniquePtr<PkgIterator>;
+
 		/// Return the pointer to the start of the PkgIterator.
 		///
 		/// # Safety
@@ -0,0 +1,206 @@
+//! Check which versions in an archive can be installed.
+//!
+//! This works like `dose-debcheck`. Each version is marked for install on its
+//! own, and if the dependencies can't be satisfied, even with the problem
+//! resolver, it is reported along with what is broken.
+//!
+//! This is meant for a cache of a repository snapshot, like one opened with
+//! [`crate::CacheBuilder::isolated`]. The packages in the status file are
+//! treated as installed, so use an empty status to check the archive alone.
+use std::collections::HashSet;
+
+use crate::cache::PackageSort;
+use crate::depcache::Savepoint;
+use crate::error::AptErrors;
+use crate::util::BrokenReport;
+use crate::{Cache, Package, Version};
+
+/// Determines which versions are checked.
+///
+/// By default every version that isn't only in the dpkg status is checked.
+///
+/// # Example:
+/// ```
+/// use oma_apt::check::InstallCheck;
+/// use oma_apt::cache::CacheBuilder;
+///
+/// let cache = CacheBuilder::new()
+///     .isolated()
+///     .local_files(&["tests/files/cache/Packages"])
+///     .build()
+///     .unwrap();
+///
+/// for bad in cache.check_installable(&InstallCheck::new()).unwrap() {
+///     println!("{} {} can't be installed", bad.version.parent().name(), bad.version.version());
+///     for report in &bad.broken {
+///         println!("  {report}");
+///     }
+/// }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct InstallCheck {
+	archive: Option<String>,
+	candidates_only: bool,
+}
+
+impl InstallCheck {
+	pub fn new() -> InstallCheck { InstallCheck::default() }
+
+	/// Only check versions from this archive or codename. ex: `stable`
+	pub fn archive(mut self, archive: &str) -> InstallCheck {
+		self.archive = Some(archive.to_string());
+		self
+	}
+
+	/// Only check the candidate of each package instead of every version.
+	pub fn candidates_only(mut self) -> InstallCheck {
+		self.candidates_only = true;
+		self
+	}
+
+	/// True if the version should be checked.
+	fn matches(&self, ver: &Version) -> bool {
+		ver.package_files().any(|pkg_file| {
+			let archive = pkg_file.archive().unwrap_or_default();
+			match &self.archive {
+				Some(wanted) => {
+					archive == wanted || pkg_file.codename().is_some_and(|c| c == wanted)
+				},
+				// Versions that are only installed aren't part of an archive.
+				None => archive != "now",
+			}
+		})
+	}
+}
+
+/// A version that can't be installed.
+#[derive(Debug)]
+pub struct Uninstallable<'a> {
+	pub version: Version<'a>,
+	/// What is broken when only this version is marked for install.
+	///
+	/// The first report is for the package of the version if it is broken,
+	/// followed by any other package that the install breaks.
+	pub broken: Vec<BrokenReport>,
+}
+
+/// The packages that are broken but weren't before the trial.
+fn newly_broken<'a, 'b>(
+	cache: &Cache,
+	pkgs: &'b [Package<'a>],
+	already: &HashSet<u64>,
+) -> Vec<&'b Package<'a>> {
+	// Nothing needs to be looked at if nothing is broken.
+	if cache.depcache().broken_count() == 0 {
+		return vec![];
+	}
+
+	pkgs.iter()
+		.filter(|pkg| pkg.is_inst_broken() && !already.contains(&pkg.index()))
+		.collect()
+}
+
+/// Reports for the package and everything else that is newly broken.
+fn reports(cache: &Cache, pkg: &Package, others: &[&Package]) -> Vec<BrokenReport> {
+	let mut broken: Vec<BrokenReport> = BrokenReport::new(cache, pkg, false).into_iter().collect();
+
+	for other in others {
+		if other.index() != pkg.index() {
+			broken.extend(BrokenReport::new(cache, other, false));
+		}
+	}
+	broken
+}
+
+pub(crate) fn check_installable<'a>(
+	cache: &'a Cache,
+	check: &InstallCheck,
+) -> Result<Vec<Uninstallable<'a>>, AptErrors> {
+	let depcache = cache.depcache();
+	let save = depcache.savepoint();
+
+	// Each trial starts with nothing marked,
+	// so it can be undone by keeping the packages it changed.
+	let marked = cache.get_changes(false).next().is_some();
+	if marked {
+		depcache.clear_marked()?;
+	}
+	let clean = depcache.savepoint();
+	// What is garbage is only worked out once, when the group is released.
+	let group = depcache.action_guard();
+
+	let pkgs: Vec<Package> = cache.packages(&PackageSort::default().names()).collect();
+	// Packages that are broken right now, so they aren't blamed on a version.
+	let already: HashSet<u64> = pkgs
+		.iter()
+		.filter(|pkg| pkg.is_inst_broken())
+		.map(|pkg| pkg.index())
+		.collect();
+
+	let mut versions = vec![];
+	for pkg in &pkgs {
+		if check.candidates_only {
+			versions.extend(pkg.candidate());
+		} else {
+			versions.extend(pkg.versions());
+		}
+	}
+	versions.retain(|ver| check.matches(ver));
+
+	let found = trials(cache, &pkgs, versions, &already, &clean);
+
+	group.release();
+	// Nothing is left marked, even if a trial failed.
+	if marked || found.is_err() {
+		depcache.restore(&save)?;
+	}
+	found
+}
+
+/// Mark each version for install on its own and keep the ones that fail.
+fn trials<'a>(
+	cache: &'a Cache,
+	pkgs: &[Package<'a>],
+	versions: Vec<Version<'a>>,
+	already: &HashSet<u64>,
+	clean: &Savepoint,
+) -> Result<Vec<Uninstallable<'a>>, AptErrors> {
+	let depcache = cache.depcache();
+
+	let mut found = vec![];
+	for ver in versions {
+		let pkg = ver.parent();
+		ver.set_candidate();
+		pkg.mark_install(true, true);
+
+		let installed = |pkg: &Package| {
+			pkg.install_version()
+				.is_some_and(|inst| inst.index() == ver.index())
+				&& !pkg.is_inst_broken()
+				&& newly_broken(cache, pkgs, already).is_empty()
+		};
+
+		if !installed(&pkg) {
+			// Keep what is broken before the resolver changes anything,
+			// this shows the missing dependency most clearly.
+			let broken = reports(cache, &pkg, &newly_broken(cache, pkgs, already));
+
+			pkg.protect();
+			let resolved = cache.resolve(false).is_ok() && installed(&pkg);
+			cache.resolver().clear(&pkg);
+
+			if !resolved {
+				found.push(Uninstallable {
+					version: ver,
+					broken,
+				});
+			}
+		}
+
+		// Only the packages that the trial changed are marked again.
+		if !depcache.restore_kept(clean)? {
+			depcache.restore(clean)?;
+		}
+	}
+	Ok(found)
+}
@@ -177,6 +177,108 @@ impl Config {
 	}
 }
 
+/// The value of a key before a [`ConfigScope`] changed it.
+#[derive(Debug)]
+struct SavedValue {
+	key: String,
+	value: Option<String>,
+	list: Vec<String>,
+}
+
+/// Change configuration values until the scope is dropped.
+///
+/// The configuration in apt is global to the process. When the scope is
+/// dropped every key that was changed through it is put back the way it was.
+///
+/// Example:
+/// ```
+/// use oma_apt::config::{Config, ConfigScope};
+///
+/// let config = Config::new();
+/// let status = config.file("Dir::State::status", "");
+/// {
+///     let mut scope = ConfigScope::new();
+///     scope.set("Dir::State::status", "/tmp/status");
+///     assert_eq!(config.file("Dir::State::status", ""), "/tmp/status");
+/// }
+/// assert_eq!(config.file("Dir::State::status", ""), status);
+/// ```
+#[derive(Debug)]
+pub struct ConfigScope {
+	config: Config,
+	saved: Vec<SavedValue>,
+}
+
+impl Default for ConfigScope {
+	fn default() -> Self { Self::new() }
+}
+
+impl ConfigScope {
+	/// Start a new scope. The config system is initialized if needed.
+	pub fn new() -> Self {
+		Self {
+			config: Config::new(),
+			saved: vec![],
+		}
+	}
+
+	/// Remember the value of a key the first time it's changed.
+	///
+	/// This is also used for keys that something else will change,
+	/// so they are put back as well.
+	pub(crate) fn save(&mut self, key: &str) {
+		if self.saved.iter().any(|saved| saved.key == key) {
+			return;
+		}
+
+		let is_list = self
+			.config
+			.tree(key)
+			.is_some_and(|tree| tree.child().is_some());
+
+		self.saved.push(SavedValue {
+			key: key.to_string(),
+			value: self.config.get(key),
+			list: if is_list { self.config.find_vector(key) } else { vec![] },
+		});
+	}
+
+	/// Set the given key to the specified value.
+	pub fn set(&mut self, key: &str, value: &str) {
+		self.save(key);
+		self.config.set(key, value);
+	}
+
+	/// Replace a configuration list with new values.
+	pub fn set_vector(&mut self, key: &str, values: &Vec<&str>) {
+		self.save(key);
+		self.config.clear(key);
+		self.config.set_vector(key, values);
+	}
+
+	/// Clear all values from a key.
+	pub fn clear(&mut self, key: &str) {
+		self.save(key);
+		self.config.clear(key);
+	}
+}
+
+impl Drop for ConfigScope {
+	fn drop(&mut self) {
+		// Restore in reverse in case a key was nested in another.
+		for saved in self.saved.iter().rev() {
+			self.config.clear(&saved.key);
+			if let Some(value) = &saved.value {
+				self.config.set(&saved.key, value);
+			}
+			if !saved.list.is_empty() {
+				self.config
+					.set_vector(&saved.key, &saved.list.iter().map(|v| v.as_str()).collect());
+			}
+		}
+	}
+}
+
 pub struct ConfigTree {
 	pub ptr: UniquePtr<raw::ConfigTree>,
 }
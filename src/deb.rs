@@ -0,0 +1,759 @@
+//! Read `.deb` archives without dpkg-deb.
+//!
+//! A `.deb` is an `ar` archive with three members, `debian-binary`,
+//! `control.tar` and `data.tar`. The tar members can be compressed with
+//! gzip, xz or zstd, or not at all.
+//!
+//! The control tar is small, so it is read when the file is opened. The data
+//! tar is only read as a stream through [`DebFile::data_tar`].
+//!
+//! [`DebBuilder`] does the opposite and builds a `.deb` from a directory.
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use tar::{Archive, EntryType, Header};
+use xz2::read::XzDecoder;
+use xz2::write::XzEncoder;
+
+use crate::error::AptErrors;
+use crate::tagfile::TagSection;
+use crate::util::hash_file;
+
+/// The names of the maintainer scripts that can be in the control tar.
+pub const MAINTAINER_SCRIPTS: [&str; 5] = ["preinst", "postinst", "prerm", "postrm", "config"];
+
+/// A member of the `ar` archive.
+#[derive(Debug, Clone)]
+struct Member {
+	name: String,
+	/// Where the content starts in the file.
+	offset: u64,
+	size: u64,
+}
+
+/// A file from the control tar. ex: `postinst` or `conffiles`
+#[derive(Debug, Clone)]
+pub struct ControlFile {
+	/// The name of the file without the leading `./`
+	pub name: String,
+	/// The permission bits of the file.
+	pub mode: u32,
+	pub content: Vec<u8>,
+}
+
+impl ControlFile {
+	/// True if dpkg can run the file, which is the case for maintainer scripts.
+	pub fn is_executable(&self) -> bool { self.mode & 0o111 != 0 }
+}
+
+/// A binary package archive.
+///
+/// # Example:
+/// ```
+/// use oma_apt::deb::DebFile;
+///
+/// let deb = DebFile::open("tests/files/cache/apt.deb").unwrap();
+/// println!("{}", deb.control().get("Package").unwrap());
+///
+/// for entry in deb.data_tar().unwrap().entries().unwrap() {
+///     println!("{}", entry.unwrap().path().unwrap().display());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DebFile {
+	path: PathBuf,
+	format: String,
+	control_member: Member,
+	data_member: Member,
+	control: TagSection,
+	control_files: Vec<ControlFile>,
+}
+
+impl DebFile {
+	/// Open a `.deb` and read its control tar.
+	pub fn open<P: AsRef<Path>>(path: P) -> Result<DebFile, AptErrors> {
+		let path = path.as_ref().to_path_buf();
+		let members = read_members(&mut File::open(&path)?)
+			.map_err(|err| AptErrors::from(format!("{}: {err}", path.display())))?;
+
+		let find = |prefix: &str| {
+			members
+				.iter()
+				.find(|member| member.name.starts_with(prefix))
+				.cloned()
+				.ok_or_else(|| {
+					AptErrors::from(format!(
+						"{} is not a Debian package, '{prefix}' is missing",
+						path.display()
+					))
+				})
+		};
+
+		let binary = find("debian-binary")?;
+		let control_member = find("control.tar")?;
+		let data_member = find("data.tar")?;
+
+		let mut format = String::new();
+		member_reader(&path, &binary)?.read_to_string(&mut format)?;
+		let format = format.trim().to_string();
+		if !format.starts_with("2.") {
+			return Err(AptErrors::from(format!(
+				"{} has unsupported format '{format}'",
+				path.display()
+			)));
+		}
+
+		let control_files = read_control_files(decompress(&path, &control_member)?)?;
+		let control = control_files
+			.iter()
+			.find(|file| file.name == "control")
+			.ok_or_else(|| AptErrors::from(format!("{} has no control file", path.display())))?;
+
+		let control = TagSection::new(String::from_utf8_lossy(&control.content).trim_end())
+			.map_err(|err| AptErrors::from(format!("{}: {err}", path.display())))?;
+
+		Ok(DebFile {
+			path,
+			format,
+			control_member,
+			data_member,
+			control,
+			control_files,
+		})
+	}
+
+	/// The path the file was opened from.
+	pub fn path(&self) -> &Path { &self.path }
+
+	/// The content of `debian-binary`. ex: `2.0`
+	pub fn format(&self) -> &str { &self.format }
+
+	/// The control stanza of the package.
+	pub fn control(&self) -> &TagSection { &self.control }
+
+	/// Every file in the control tar.
+	pub fn control_files(&self) -> &[ControlFile] { &self.control_files }
+
+	/// A file from the control tar by name. ex: `md5sums`
+	pub fn control_file(&self, name: &str) -> Option<&ControlFile> {
+		self.control_files.iter().find(|file| file.name == name)
+	}
+
+	/// The maintainer scripts that the package ships.
+	pub fn maintainer_scripts(&self) -> impl Iterator<Item = &ControlFile> {
+		self.control_files
+			.iter()
+			.filter(|file| MAINTAINER_SCRIPTS.contains(&file.name.as_str()))
+	}
+
+	/// The paths listed in `conffiles`.
+	///
+	/// Flags such as `remove-on-upgrade` are left out.
+	pub fn conffiles(&self) -> Vec<String> {
+		let Some(file) = self.control_file("conffiles") else {
+			return vec![];
+		};
+
+		String::from_utf8_lossy(&file.content)
+			.lines()
+			.map(str::trim)
+			.filter(|line| !line.is_empty())
+			.map(|line| match line.starts_with('/') {
+				true => line.to_string(),
+				false => line
+					.split_once(' ')
+					.map_or(line, |(_, path)| path.trim())
+					.to_string(),
+			})
+			.collect()
+	}
+
+	/// Stream the control tar.
+	pub fn control_tar(&self) -> Result<DebTar, AptErrors> {
+		let reader = decompress(&self.path, &self.control_member)?;
+		Ok(DebTar(Archive::new(reader)))
+	}
+
+	/// Stream the data tar, which holds the files that get installed.
+	pub fn data_tar(&self) -> Result<DebTar, AptErrors> {
+		let reader = decompress(&self.path, &self.data_member)?;
+		Ok(DebTar(Archive::new(reader)))
+	}
+}
+
+/// A tar member of a `.deb`, read as a stream.
+pub struct DebTar(Archive<Box<dyn Read>>);
+
+impl DebTar {
+	/// The entries of the tar. They can only be read once, in order.
+	pub fn entries(&mut self) -> Result<DebTarEntries<'_>, AptErrors> {
+		Ok(DebTarEntries(self.0.entries()?))
+	}
+}
+
+/// An iterator over the entries of a [`DebTar`].
+pub struct DebTarEntries<'a>(tar::Entries<'a, Box<dyn Read>>);
+
+impl<'a> Iterator for DebTarEntries<'a> {
+	type Item = Result<DebTarEntry<'a>, AptErrors>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		Some(self.0.next()?.map(DebTarEntry).map_err(AptErrors::from))
+	}
+}
+
+/// A file, directory or link in a [`DebTar`].
+///
+/// Reading the entry gives the content of a file.
+pub struct DebTarEntry<'a>(tar::Entry<'a, Box<dyn Read>>);
+
+impl DebTarEntry<'_> {
+	/// The path in the archive. ex: `./usr/bin/apt`
+	pub fn path(&self) -> Result<PathBuf, AptErrors> { Ok(self.0.path()?.into_owned()) }
+
+	/// The target of a symlink or a hard link.
+	pub fn link_name(&self) -> Result<Option<PathBuf>, AptErrors> {
+		Ok(self.0.link_name()?.map(|path| path.into_owned()))
+	}
+
+	/// The permission bits of the entry.
+	pub fn mode(&self) -> Result<u32, AptErrors> { Ok(self.0.header().mode()?) }
+
+	/// The size of the content in bytes.
+	pub fn size(&self) -> u64 { self.0.size() }
+
+	pub fn is_file(&self) -> bool { self.0.header().entry_type().is_file() }
+
+	pub fn is_dir(&self) -> bool { self.0.header().entry_type().is_dir() }
+
+	pub fn is_symlink(&self) -> bool { self.0.header().entry_type().is_symlink() }
+
+	/// Write the entry to `dst`, which is the full path including the name.
+	pub fn unpack<P: AsRef<Path>>(&mut self, dst: P) -> Result<(), AptErrors> {
+		self.0.unpack(dst)?;
+		Ok(())
+	}
+}
+
+impl Read for DebTarEntry<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { self.0.read(buf) }
+}
+
+/// How the tar members of a `.deb` are compressed.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum Compression {
+	None,
+	Gzip,
+	/// The default of dpkg-deb.
+	#[default]
+	Xz,
+	Zstd,
+}
+
+impl Compression {
+	/// The file extension, including the dot. ex: `.xz`
+	pub fn extension(&self) -> &'static str {
+		match self {
+			Compression::None => "",
+			Compression::Gzip => ".gz",
+			Compression::Xz => ".xz",
+			Compression::Zstd => ".zst",
+		}
+	}
+
+	/// Compress all of the data.
+	pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, AptErrors> {
+		let mut encoder = self.encoder(vec![])?;
+		encoder.write_all(data)?;
+		Ok(encoder.finish()?)
+	}
+
+	/// Compress everything that is written to `out`.
+	fn encoder<W: Write>(&self, out: W) -> io::Result<Encoder<W>> {
+		Ok(match self {
+			Compression::None => Encoder::None(out),
+			Compression::Gzip => Encoder::Gzip(GzEncoder::new(out, flate2::Compression::default())),
+			Compression::Xz => Encoder::Xz(XzEncoder::new(out, 6)),
+			Compression::Zstd => Encoder::Zstd(zstd::Encoder::new(out, 0)?),
+		})
+	}
+}
+
+/// A writer that compresses with one of the [`Compression`] formats.
+enum Encoder<W: Write> {
+	None(W),
+	Gzip(GzEncoder<W>),
+	Xz(XzEncoder<W>),
+	Zstd(zstd::Encoder<'static, W>),
+}
+
+impl<W: Write> Encoder<W> {
+	/// Write the end of the compressed stream and return the writer.
+	fn finish(self) -> io::Result<W> {
+		match self {
+			Encoder::None(out) => Ok(out),
+			Encoder::Gzip(encoder) => encoder.finish(),
+			Encoder::Xz(encoder) => encoder.finish(),
+			Encoder::Zstd(encoder) => encoder.finish(),
+		}
+	}
+}
+
+impl<W: Write> Write for Encoder<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		match self {
+			Encoder::None(out) => out.write(buf),
+			Encoder::Gzip(encoder) => encoder.write(buf),
+			Encoder::Xz(encoder) => encoder.write(buf),
+			Encoder::Zstd(encoder) => encoder.write(buf),
+		}
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		match self {
+			Encoder::None(out) => out.flush(),
+			Encoder::Gzip(encoder) => encoder.flush(),
+			Encoder::Xz(encoder) => encoder.flush(),
+			Encoder::Zstd(encoder) => encoder.flush(),
+		}
+	}
+}
+
+/// Build a `.deb` from a control stanza and a directory.
+///
+/// The directory holds the files as they are installed. Like dpkg-deb, a
+/// `DEBIAN` directory in it is not installed, the files in it are put in the
+/// control tar instead. ex: `DEBIAN/postinst` or `DEBIAN/conffiles`
+///
+/// `Installed-Size` is set from the files, and `md5sums` is generated.
+/// Everything is owned by root.
+///
+/// # Example:
+/// ```no_run
+/// use oma_apt::deb::{Compression, DebBuilder};
+/// use oma_apt::tagfile::TagSection;
+/// use oma_apt::new_cache;
+///
+/// let control = TagSection::new(
+///     "Package: my-meta\nVersion: 1.0\nArchitecture: all\nDepends: apt\n\
+///      Maintainer: Foo Bar <foobar@example.com>\nDescription: A meta package",
+/// )
+/// .unwrap();
+///
+/// DebBuilder::new(control, "staging")
+///     .compression(Compression::Gzip)
+///     .build("my-meta.deb")
+///     .unwrap();
+///
+/// let cache = new_cache!(&["my-meta.deb"]).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct DebBuilder {
+	control: TagSection,
+	root: PathBuf,
+	compression: Compression,
+	control_files: Vec<ControlFile>,
+}
+
+impl DebBuilder {
+	pub fn new<P: AsRef<Path>>(control: TagSection, root: P) -> DebBuilder {
+		DebBuilder {
+			control,
+			root: root.as_ref().to_path_buf(),
+			compression: Compression::default(),
+			control_files: vec![],
+		}
+	}
+
+	/// How to compress the control and data tar.
+	pub fn compression(mut self, compression: Compression) -> DebBuilder {
+		self.compression = compression;
+		self
+	}
+
+	/// Add a file to the control tar. ex: `postinst` or `conffiles`
+	///
+	/// Maintainer scripts are made executable.
+	/// This replaces a file with the same name from the `DEBIAN` directory.
+	pub fn control_file(mut self, name: &str, content: &[u8]) -> DebBuilder {
+		let mode = match MAINTAINER_SCRIPTS.contains(&name) {
+			true => 0o755,
+			false => 0o644,
+		};
+
+		self.control_files.retain(|file| file.name != name);
+		self.control_files.push(ControlFile {
+			name: name.to_string(),
+			mode,
+			content: content.to_vec(),
+		});
+		self
+	}
+
+	/// Write the package to `path`.
+	pub fn build<P: AsRef<Path>>(&self, path: P) -> Result<(), AptErrors> {
+		for key in ["Package", "Version", "Architecture"] {
+			if self.control.get(key).is_none() {
+				return Err(AptErrors::from(format!(
+					"The control stanza is missing '{key}'"
+				)));
+			}
+		}
+
+		let mut entries = vec![];
+		walk_dir(&self.root, Path::new(""), &mut entries)?;
+
+		// This is how dpkg-gencontrol counts the size of the whole directory,
+		// in KiB. Files and symlinks are rounded up and hard links are only
+		// counted once. Every other inode, like a directory, is 1 KiB.
+		let mut installed_size = 1;
+		let mut hard_links = HashSet::new();
+		for rel in &entries {
+			let meta = fs::symlink_metadata(self.root.join(rel))?;
+			if !(meta.is_file() || meta.is_symlink()) {
+				installed_size += 1;
+			} else if meta.nlink() == 1 || hard_links.insert((meta.dev(), meta.ino())) {
+				installed_size += meta.len().div_ceil(1024);
+			}
+		}
+
+		// The DEBIAN directory isn't installed, its files go in the control tar.
+		let (debian, entries): (Vec<_>, Vec<_>) = entries
+			.into_iter()
+			.partition(|rel| rel.starts_with("DEBIAN"));
+
+		let mut control_files = vec![];
+		for rel in debian {
+			let full = self.root.join(&rel);
+			let meta = fs::symlink_metadata(&full)?;
+			if meta.is_file() && rel.parent() == Some(Path::new("DEBIAN")) {
+				control_files.push(ControlFile {
+					name: rel.strip_prefix("DEBIAN").unwrap().display().to_string(),
+					mode: meta.permissions().mode() & 0o7777,
+					content: fs::read(&full)?,
+				});
+			}
+		}
+
+		let mut md5sums = String::new();
+		for rel in &entries {
+			let full = self.root.join(rel);
+			if fs::symlink_metadata(&full)?.is_file() {
+				md5sums += &format!("{}  {}\n", hash_file(&full, "MD5Sum")?, rel.display());
+			}
+		}
+
+		for file in &self.control_files {
+			control_files.retain(|f| f.name != file.name);
+			control_files.push(file.clone());
+		}
+		control_files.retain(|f| f.name != "control" && f.name != "md5sums");
+
+		let mut control = self.control.clone();
+		control.set("Installed-Size", &installed_size.to_string());
+
+		// The tars are compressed straight into the file, the sizes of the
+		// members are filled in after.
+		let ext = self.compression.extension();
+		let mut out = io::BufWriter::new(File::create(path)?);
+		out.write_all(b"!<arch>\n")?;
+		write_member(&mut out, "debian-binary", b"2.0\n")?;
+
+		let start = start_member(&mut out, &format!("control.tar{ext}"))?;
+		let mut control_tar = tar::Builder::new(self.compression.encoder(&mut out)?);
+		append_dir(&mut control_tar, "./", &fs::metadata(&self.root)?)?;
+		append_file(
+			&mut control_tar,
+			"./control",
+			0o644,
+			control.to_string().as_bytes(),
+		)?;
+		if !md5sums.is_empty() {
+			append_file(&mut control_tar, "./md5sums", 0o644, md5sums.as_bytes())?;
+		}
+		for file in &control_files {
+			append_file(
+				&mut control_tar,
+				&format!("./{}", file.name),
+				file.mode,
+				&file.content,
+			)?;
+		}
+		control_tar.into_inner()?.finish()?;
+		end_member(&mut out, start)?;
+
+		let start = start_member(&mut out, &format!("data.tar{ext}"))?;
+		let mut data = tar::Builder::new(self.compression.encoder(&mut out)?);
+		append_dir(&mut data, "./", &fs::metadata(&self.root)?)?;
+		for rel in entries {
+			let full = self.root.join(&rel);
+			let meta = fs::symlink_metadata(&full)?;
+			let name = format!("./{}", rel.display());
+
+			if meta.is_dir() {
+				append_dir(&mut data, &format!("{name}/"), &meta)?;
+			} else if meta.is_symlink() {
+				let mut header = header(&meta, EntryType::Symlink);
+				let target = fs::read_link(&full)?;
+				match header.set_link_name(&target) {
+					Ok(_) => append(&mut data, &mut header, &name, io::empty())?,
+					// The target is too long for the header.
+					Err(_) => data.append_link(&mut header, &name, target)?,
+				}
+			} else if meta.is_file() {
+				let mut header = header(&meta, EntryType::Regular);
+				header.set_size(meta.len());
+				append(&mut data, &mut header, &name, File::open(&full)?)?;
+			}
+		}
+		data.into_inner()?.finish()?;
+		end_member(&mut out, start)?;
+
+		out.flush()?;
+		Ok(())
+	}
+}
+
+/// Every path under `root`, relative to it and sorted.
+pub(crate) fn walk_dir(root: &Path, rel: &Path, entries: &mut Vec<PathBuf>) -> io::Result<()> {
+	let mut names = fs::read_dir(root.join(rel))?
+		.map(|entry| entry.map(|entry| entry.file_name()))
+		.collect::<io::Result<Vec<_>>>()?;
+	names.sort();
+
+	for name in names {
+		let path = rel.join(name);
+		entries.push(path.clone());
+		if fs::symlink_metadata(root.join(&path))?.is_dir() {
+			walk_dir(root, &path, entries)?;
+		}
+	}
+	Ok(())
+}
+
+/// A tar header owned by root.
+fn header(meta: &fs::Metadata, entry_type: EntryType) -> Header {
+	let mut header = Header::new_gnu();
+	header.set_entry_type(entry_type);
+	header.set_mode(meta.permissions().mode() & 0o7777);
+	header.set_mtime(meta.mtime().max(0) as u64);
+	header.set_uid(0);
+	header.set_gid(0);
+	// These can't fail for names this short.
+	header.set_username("root").unwrap();
+	header.set_groupname("root").unwrap();
+	header.set_size(0);
+	header
+}
+
+fn append_dir(
+	tar: &mut tar::Builder<impl Write>,
+	name: &str,
+	meta: &fs::Metadata,
+) -> io::Result<()> {
+	let mut header = header(meta, EntryType::Directory);
+	append(tar, &mut header, name, io::empty())
+}
+
+fn append_file(
+	tar: &mut tar::Builder<impl Write>,
+	name: &str,
+	mode: u32,
+	content: &[u8],
+) -> io::Result<()> {
+	let mut header = Header::new_gnu();
+	header.set_entry_type(EntryType::Regular);
+	header.set_mode(mode);
+	header.set_mtime(now());
+	header.set_username("root").unwrap();
+	header.set_groupname("root").unwrap();
+	header.set_size(content.len() as u64);
+	append(tar, &mut header, name, content)
+}
+
+/// Add an entry, keeping the leading `./` of the name like dpkg-deb.
+///
+/// The tar crate drops it when it sets the path,
+/// so it is only used for names that don't fit in the header.
+fn append(
+	tar: &mut tar::Builder<impl Write>,
+	header: &mut Header,
+	name: &str,
+	data: impl Read,
+) -> io::Result<()> {
+	let field = &mut header.as_old_mut().name;
+	if name.len() > field.len() {
+		return tar.append_data(header, name, data);
+	}
+
+	field[..name.len()].copy_from_slice(name.as_bytes());
+	header.set_cksum();
+	tar.append(header, data)
+}
+
+fn now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_or(0, |time| time.as_secs())
+}
+
+/// The size of the header of an `ar` member.
+const MEMBER_HEADER_SIZE: u64 = 60;
+
+/// Write the header of an `ar` member.
+fn write_member_header(out: &mut impl Write, name: &str, size: u64) -> io::Result<()> {
+	writeln!(
+		out,
+		"{name:<16}{:<12}{:<6}{:<6}{:<8}{size:<10}`",
+		now(),
+		0,
+		0,
+		100644,
+	)
+}
+
+/// Write a member of an `ar` archive.
+fn write_member(out: &mut impl Write, name: &str, content: &[u8]) -> io::Result<()> {
+	write_member_header(out, name, content.len() as u64)?;
+	out.write_all(content)?;
+	if content.len() % 2 == 1 {
+		out.write_all(b"\n")?;
+	}
+	Ok(())
+}
+
+/// Start a member of an `ar` archive whose size isn't known yet.
+///
+/// Returns where the member starts, which is given to [`end_member`] once
+/// the content is written.
+fn start_member(out: &mut (impl Write + Seek), name: &str) -> io::Result<u64> {
+	let start = out.stream_position()?;
+	write_member_header(out, name, 0)?;
+	Ok(start)
+}
+
+/// Fill in the size of the member that was started at `start`.
+fn end_member(out: &mut (impl Write + Seek), start: u64) -> io::Result<()> {
+	let end = out.stream_position()?;
+	let size = end - start - MEMBER_HEADER_SIZE;
+
+	// The size is the last field before the closing "`\n".
+	out.seek(SeekFrom::Start(start + MEMBER_HEADER_SIZE - 12))?;
+	write!(out, "{size:<10}")?;
+	out.seek(SeekFrom::Start(end))?;
+
+	if size % 2 == 1 {
+		out.write_all(b"\n")?;
+	}
+	Ok(())
+}
+
+/// Read the member headers of an `ar` archive.
+fn read_members(file: &mut File) -> io::Result<Vec<Member>> {
+	let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+	let mut magic = [0u8; 8];
+	file.read_exact(&mut magic)
+		.map_err(|_| invalid("not an ar archive"))?;
+	if &magic != b"!<arch>\n" {
+		return Err(invalid("not an ar archive"));
+	}
+
+	let len = file.metadata()?.len();
+	let mut offset = 8;
+	let mut members = vec![];
+
+	while offset < len {
+		let mut header = [0u8; 60];
+		file.seek(SeekFrom::Start(offset))?;
+		file.read_exact(&mut header)
+			.map_err(|_| invalid("truncated ar header"))?;
+
+		if &header[58..] != b"`\n" {
+			return Err(invalid("bad ar header"));
+		}
+
+		// GNU ar ends names with a '/'
+		let name = String::from_utf8_lossy(&header[..16])
+			.trim_end()
+			.trim_end_matches('/')
+			.to_string();
+
+		let size: u64 = std::str::from_utf8(&header[48..58])
+			.ok()
+			.and_then(|size| size.trim().parse().ok())
+			.ok_or_else(|| invalid("bad ar member size"))?;
+
+		offset += 60;
+		if offset + size > len {
+			return Err(invalid("truncated ar member"));
+		}
+
+		members.push(Member { name, offset, size });
+		// Members are padded to an even offset.
+		offset += size + size % 2;
+	}
+
+	Ok(members)
+}
+
+/// A reader of only the content of the member.
+fn member_reader(path: &Path, member: &Member) -> Result<io::Take<File>, AptErrors> {
+	let mut file = File::open(path)?;
+	file.seek(SeekFrom::Start(member.offset))?;
+	Ok(file.take(member.size))
+}
+
+/// Pick the decompressor from the extension of the member.
+fn decompress(path: &Path, member: &Member) -> Result<Box<dyn Read>, AptErrors> {
+	let reader = member_reader(path, member)?;
+
+	Ok(match member.name.rsplit_once('.') {
+		Some((_, "gz")) => Box::new(GzDecoder::new(reader)),
+		Some((_, "xz")) => Box::new(XzDecoder::new(reader)),
+		Some((_, "zst")) => Box::new(zstd::Decoder::new(reader)?),
+		Some((_, "tar")) => Box::new(reader),
+		_ => {
+			return Err(AptErrors::from(format!(
+				"{}: unsupported compression for '{}'",
+				path.display(),
+				member.name
+			)));
+		},
+	})
+}
+
+fn read_control_files(reader: Box<dyn Read>) -> Result<Vec<ControlFile>, AptErrors> {
+	let mut files = vec![];
+
+	for entry in Archive::new(reader).entries()? {
+		let mut entry = entry?;
+		if !entry.header().entry_type().is_file() {
+			continue;
+		}
+
+		let path = entry.path()?;
+		let name = path
+			.strip_prefix("./")
+			.unwrap_or(&path)
+			.display()
+			.to_string();
+		let mode = entry.header().mode()?;
+
+		let mut content = vec![];
+		entry.read_to_end(&mut content)?;
+		files.push(ControlFile {
+			name,
+			mode,
+			content,
+		});
+	}
+
+	Ok(files)
+}
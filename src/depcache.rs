@@ -30,26 +30,191 @@
 //!
 //! The Candidate version is what is shown the 'Install Version' field.
 
+use std::marker::PhantomData;
+
 use cxx::UniquePtr;
 
+use crate::config::{init_config_system, Config, ConfigScope};
 use crate::error::AptErrors;
 use crate::progress::OperationProgress;
-use crate::raw::PkgDepCache;
-use crate::util::DiskSpace;
+use crate::raw::{ActionGroup, DepCacheSavepoint, PkgDepCache};
+use crate::util::{apt_lock, apt_unlock, DiskSpace};
 
 /// Dependency Extension data for the cache.
 pub struct DepCache {
 	pub(crate) ptr: UniquePtr<PkgDepCache>,
+	/// Where the extended states and the dpkg lock were when the depcache
+	/// was made. See [`DepCache::write_state_file`].
+	state_files: Vec<(&'static str, String)>,
 }
 
 impl DepCache {
-	pub fn new(ptr: UniquePtr<PkgDepCache>) -> DepCache { DepCache { ptr } }
+	pub fn new(ptr: UniquePtr<PkgDepCache>) -> DepCache {
+		let config = Config::new();
+		let state_files = [
+			(
+				"Dir::State::extended_states",
+				"/var/lib/apt/extended_states",
+			),
+			("Dir::State::status", "/var/lib/dpkg/status"),
+		]
+		.into_iter()
+		.map(|(key, default)| (key, config.file(key, default)))
+		.collect();
+		DepCache { ptr, state_files }
+	}
 
 	/// Clear any marked changes in the DepCache.
 	pub fn clear_marked(&self) -> Result<(), AptErrors> {
 		Ok(self.init(OperationProgress::quiet().pin().as_mut())?)
 	}
 
+	/// Start an [`ActionGroup`] that is released when the guard is dropped.
+	///
+	/// Unlike [`raw::PkgDepCache::action_group`] this is safe, because the
+	/// guard borrows the DepCache and so can't outlive the cache.
+	///
+	/// # Example:
+	/// ```
+	/// use oma_apt::new_cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// let guard = cache.depcache().action_guard();
+	///
+	/// for pkg in cache.iter() {
+	///     pkg.mark_keep();
+	/// }
+	///
+	/// // Or just let it leave scope
+	/// guard.release();
+	/// ```
+	pub fn action_guard(&self) -> ActionGroupGuard<'_> {
+		ActionGroupGuard {
+			ptr: unsafe { self.ptr.action_group() },
+			_depcache: PhantomData,
+		}
+	}
+
+	/// Run `f` inside of an [`ActionGroup`] and return what it returns.
+	///
+	/// Cleanup that libapt normally does after every mark is delayed
+	/// until `f` finishes. Use this when marking many packages at once.
+	///
+	/// # Example:
+	/// ```
+	/// use oma_apt::new_cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	///
+	/// let marked = cache.depcache().batch(|| {
+	///     cache.iter().filter(|pkg| pkg.mark_keep()).count()
+	/// });
+	/// ```
+	pub fn batch<T>(&self, f: impl FnOnce() -> T) -> T {
+		let guard = self.action_guard();
+		let ret = f();
+		guard.release();
+		ret
+	}
+
+	/// Write the auto installed marks to `Dir::State::extended_states`.
+	///
+	/// This is how `apt-mark auto` and `apt-mark manual` persist changes
+	/// made with [`crate::Package::mark_auto`] without installing anything.
+	///
+	/// The file and the lock are the ones that the cache was opened with, so a
+	/// cache from [`crate::CacheBuilder::root_dir`] writes under its root.
+	/// Caches without extended states, like [`crate::CacheBuilder::isolated`]
+	/// ones, can't be written.
+	///
+	/// The apt lock is held while writing, so this requires root.
+	///
+	/// # Example:
+	/// ```no_run
+	/// use oma_apt::new_cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// let pkg = cache.get("apt").unwrap();
+	///
+	/// pkg.mark_auto(false);
+	/// cache.depcache().write_state_file().unwrap();
+	/// ```
+	pub fn write_state_file(&self) -> Result<(), AptErrors> {
+		if self
+			.state_files
+			.iter()
+			.any(|(_, path)| path.is_empty() || path == "/dev/null")
+		{
+			return Err(AptErrors::from(
+				"The cache was opened without an extended states or dpkg status file".to_string(),
+			));
+		}
+
+		// The paths already have the root of the cache in them.
+		let mut scope = ConfigScope::new();
+		scope.set("RootDir", "");
+		for (key, path) in &self.state_files {
+			scope.set(key, path);
+		}
+
+		let result = apt_lock().and_then(|_| {
+			let result = self.ptr.write_state_file();
+			apt_unlock();
+			Ok(result?)
+		});
+		drop(scope);
+		// Put the system back the way it was for everyone else.
+		init_config_system();
+		result
+	}
+
+	/// Take a snapshot of the marks on every package.
+	///
+	/// This includes the mode (install, keep, delete), the candidate, the
+	/// auto flag and reinstall. Use [`DepCache::restore`] to go back to it.
+	///
+	/// # Example:
+	/// ```
+	/// use oma_apt::new_cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// let save = cache.depcache().savepoint();
+	///
+	/// let pkg = cache.get("apt").unwrap();
+	/// pkg.mark_delete(false);
+	/// cache.resolve(false).unwrap();
+	///
+	/// for change in cache.get_changes(true) {
+	///     println!("{} would change", change.name());
+	/// }
+	///
+	/// // Everything is back how it was.
+	/// cache.depcache().restore(&save).unwrap();
+	/// ```
+	pub fn savepoint(&self) -> Savepoint<'_> {
+		Savepoint {
+			ptr: unsafe { self.ptr.savepoint() },
+			_depcache: PhantomData,
+		}
+	}
+
+	/// Restore the marks from a [`Savepoint`].
+	///
+	/// Any changes made since the savepoint was taken are lost.
+	pub fn restore(&self, savepoint: &Savepoint) -> Result<(), AptErrors> {
+		Ok(self.ptr.restore(&savepoint.ptr)?)
+	}
+
+	/// Restore a [`Savepoint`] by keeping the packages that changed since.
+	///
+	/// This is much faster than [`DepCache::restore`] when only a few packages
+	/// changed, as the rest of the cache isn't updated. It returns false if a
+	/// changed package wasn't kept in the savepoint, and
+	/// [`DepCache::restore`] has to be used instead.
+	pub(crate) fn restore_kept(&self, savepoint: &Savepoint) -> Result<bool, AptErrors> {
+		Ok(self.ptr.restore_kept(&savepoint.ptr)?)
+	}
+
 	/// The amount of space required for installing/removing the packages."
 	///
 	/// i.e. the Installed-Size of all packages marked for installation"
@@ -63,6 +228,29 @@ impl DepCache {
 	}
 }
 
+/// An active [`ActionGroup`] that can not outlive the cache.
+///
+/// Created with [`DepCache::action_guard`].
+pub struct ActionGroupGuard<'a> {
+	ptr: UniquePtr<ActionGroup>,
+	_depcache: PhantomData<&'a DepCache>,
+}
+
+impl ActionGroupGuard<'_> {
+	/// Release the ActionGroup now instead of when it leaves scope.
+	///
+	/// This will trigger a MarkAndSweep.
+	pub fn release(mut self) { self.ptr.pin_mut().release(); }
+}
+
+/// A snapshot of the marks in the DepCache.
+///
+/// Created with [`DepCache::savepoint`].
+pub struct Savepoint<'a> {
+	ptr: UniquePtr<DepCacheSavepoint>,
+	_depcache: PhantomData<&'a DepCache>,
+}
+
 #[cxx::bridge]
 pub(crate) mod raw {
 	impl UniquePtr<PkgDepCache> {}
@@ -100,6 +288,12 @@ pub(crate) mod raw {
 		/// action_group.pin_mut().release();
 		/// ```
 		type ActionGroup;
+
+		/// The state of every package in the DepCache.
+		///
+		/// Safe to use through [`crate::Savepoint`].
+		type DepCacheSavepoint;
+
 		type PkgIterator = crate::iterators::PkgIterator;
 		type VerIterator = crate::iterators::VerIterator;
 		type DepIterator = crate::iterators::DepIterator;
@@ -125,6 +319,27 @@ pub(crate) mod raw {
 		/// MarkAndSweep
 		pub fn release(self: Pin<&mut ActionGroup>);
 
+		/// Write the auto installed marks to Dir::State::extended_states.
+		///
+		/// This does not take the apt lock.
+		pub fn write_state_file(self: &PkgDepCache) -> Result<()>;
+
+		/// Copy the state of every package so it can be restored later.
+		///
+		/// # Safety
+		///
+		/// The returned UniquePtr cannot outlive the cache.
+		unsafe fn savepoint(self: &PkgDepCache) -> UniquePtr<DepCacheSavepoint>;
+
+		/// Put every package back to the state it was in
+		/// when the savepoint was made.
+		pub fn restore(self: &PkgDepCache, savepoint: &DepCacheSavepoint) -> Result<()>;
+
+		/// Keep the packages that changed since the savepoint.
+		///
+		/// Returns false if a changed package wasn't kept in the savepoint.
+		pub fn restore_kept(self: &PkgDepCache, savepoint: &DepCacheSavepoint) -> Result<bool>;
+
 		/// Perform an Upgrade.
 		///
 		/// ## mark_auto:
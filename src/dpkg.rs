@@ -0,0 +1,702 @@
+//! Read and write the dpkg database.
+//!
+//! Selections work like `apt-mark hold` and `dpkg --get-selections` /
+//! `dpkg --set-selections`. Changes are written by running dpkg, which honors
+//! `Dir::State::status` as the admin directory and `DPkg::Chroot-Directory`.
+//!
+//! The cache is not updated after selections are written.
+//! Create a new cache to see the new selected states.
+//!
+//! Conffiles are read from the dpkg status and compared with the files on disk
+//! under the root dir of the cache.
+//!
+//! The file lists and md5sums are read from the `info` directory of the dpkg
+//! admin dir, which is the directory of `Dir::State::status`.
+//!
+//! The root dir and the admin dir are saved when the cache is opened, so a
+//! cache from [`crate::CacheBuilder::root_dir`] keeps reading its own root.
+//!
+//! Conffile prompts are answered before dpkg runs. The new conffiles are read
+//! from the downloaded archives and compared the way dpkg does, and dpkg is
+//! given the answers with `--force-confold` or `--force-confnew`. This needs
+//! the `deb` feature.
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "deb")]
+use std::process::Command;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[cfg(feature = "deb")]
+use crate::config::Config;
+#[cfg(feature = "deb")]
+use crate::deb::DebFile;
+use crate::error::AptErrors;
+#[cfg(feature = "deb")]
+use crate::progress::ConffilePrompt;
+use crate::records::RecordField;
+use crate::util::hash_file;
+#[cfg(feature = "deb")]
+use crate::util::TempDir;
+use crate::{Cache, Package, PackageSort, PkgSelectedState, Version};
+
+/// The selected state of a single package.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct Selection {
+	/// The full name of the package. ex: `apt` or `libc6:i386`
+	pub package: String,
+	/// What the user wants for the package.
+	pub state: PkgSelectedState,
+}
+
+/// Where a path of the installed system is on disk, under `root`.
+fn root_path(root: &Path, path: &Path) -> PathBuf {
+	root.join(path.strip_prefix("/").unwrap_or(path))
+}
+
+/// The root dir of the config, which is `/` if `RootDir` isn't set.
+pub(crate) fn config_root_dir() -> PathBuf {
+	let root = crate::raw::config::find("RootDir".to_string(), "".to_string());
+	if root.is_empty() {
+		return PathBuf::from("/");
+	}
+	PathBuf::from(root)
+}
+
+/// The dpkg admin dir of the config, ex: `/var/lib/dpkg`
+///
+/// This is under `RootDir` if it is set.
+pub(crate) fn config_admin_dir() -> PathBuf {
+	let status = crate::raw::config::find_file(
+		"Dir::State::status".to_string(),
+		"/var/lib/dpkg/status".to_string(),
+	);
+	Path::new(&status)
+		.parent()
+		.map(|dir| dir.to_path_buf())
+		.unwrap_or_else(|| PathBuf::from("/var/lib/dpkg"))
+}
+
+/// Find a file of the package in the dpkg info dir.
+///
+/// `Multi-Arch: same` packages use `name:arch.ext`, the rest use `name.ext`.
+fn info_file(pkg: &Package, ext: &str) -> Option<PathBuf> {
+	let info = pkg.cache.admin_dir().join("info");
+	[
+		info.join(format!("{}:{}.{ext}", pkg.name(), pkg.arch())),
+		info.join(format!("{}.{ext}", pkg.name())),
+	]
+	.into_iter()
+	.find(|path| path.exists())
+}
+
+/// How an installed file differs from the md5sums of its package.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub enum FileChange {
+	/// The contents of the file changed.
+	Modified,
+	/// The file was removed.
+	Missing,
+	/// The file couldn't be read, so it can't be compared.
+	///
+	/// Contains the error from reading it.
+	Unreadable(String),
+}
+
+/// An installed file that doesn't match the md5sums of its package.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct ModifiedFile {
+	/// The path on the installed system. ex: `/usr/bin/apt`
+	pub path: PathBuf,
+	/// How the file differs.
+	pub change: FileChange,
+}
+
+/// Split a file of the info dir into its lines.
+///
+/// The paths in them are the names from the archive, which don't have to be
+/// UTF-8.
+fn read_lines(file: &Path) -> Result<Vec<Vec<u8>>, AptErrors> {
+	Ok(fs::read(file)?
+		.split(|byte| *byte == b'\n')
+		.filter(|line| !line.is_empty())
+		.map(|line| line.to_vec())
+		.collect())
+}
+
+/// The files that dpkg installed for the package, like `dpkg -L`.
+pub(crate) fn installed_files(pkg: &Package) -> Result<Vec<PathBuf>, AptErrors> {
+	if !pkg.is_installed() {
+		return Ok(vec![]);
+	}
+
+	let Some(list) = info_file(pkg, "list") else {
+		return Err(AptErrors::from(format!(
+			"Package '{}' does not have a file list",
+			pkg.fullname(true)
+		)));
+	};
+
+	Ok(read_lines(&list)?
+		.into_iter()
+		.map(|line| PathBuf::from(OsStr::from_bytes(&line)))
+		.collect())
+}
+
+/// Compare the installed files with the md5sums, like `dpkg --verify`.
+///
+/// Conffiles are not in the md5sums, see [`Package::changed_conffiles`].
+pub(crate) fn verify(pkg: &Package) -> Result<Vec<ModifiedFile>, AptErrors> {
+	// Not every package ships md5sums, so there is nothing to compare.
+	let Some(md5sums) = info_file(pkg, "md5sums") else {
+		return Ok(vec![]);
+	};
+
+	let mut modified = vec![];
+	for line in read_lines(&md5sums)? {
+		let Some(space) = line.iter().position(u8::is_ascii_whitespace) else {
+			continue;
+		};
+		let md5 = String::from_utf8_lossy(&line[..space]);
+		let path = Path::new("/").join(OsStr::from_bytes(line[space..].trim_ascii_start()));
+
+		let disk_path = root_path(pkg.cache.root_dir(), &path);
+		if disk_path.symlink_metadata().is_err() {
+			modified.push(ModifiedFile {
+				path,
+				change: FileChange::Missing,
+			});
+			continue;
+		}
+
+		// One file that can't be read shouldn't hide the rest.
+		let change = match hash_file(&disk_path, "MD5Sum") {
+			Ok(hash) if hash == md5 => continue,
+			Ok(_) => FileChange::Modified,
+			Err(err) => FileChange::Unreadable(err.to_string()),
+		};
+		modified.push(ModifiedFile { path, change });
+	}
+	Ok(modified)
+}
+
+/// Find the packages that installed a path, like `dpkg -S`.
+pub(crate) fn owner_of<'a>(cache: &'a Cache, path: &Path) -> Result<Vec<Package<'a>>, AptErrors> {
+	let mut path = path.as_os_str().as_bytes();
+	while let Some(parent) = path.strip_suffix(b"/") {
+		path = parent;
+	}
+	let mut owners = vec![];
+
+	for entry in fs::read_dir(cache.admin_dir().join("info"))? {
+		let list = entry?.path();
+		if list.extension().is_none_or(|ext| ext != "list") {
+			continue;
+		}
+
+		if !read_lines(&list)?.iter().any(|line| line == path) {
+			continue;
+		}
+
+		// The name is `name` or `name:arch`, which is what Cache::get takes.
+		let Some(name) = list.file_stem().map(|stem| stem.to_string_lossy()) else {
+			continue;
+		};
+		if let Some(pkg) = cache.get(&name) {
+			owners.push(pkg);
+		}
+	}
+	Ok(owners)
+}
+
+/// How a conffile on disk compares with what dpkg recorded.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum ConffileState {
+	/// The file is the same as when it was installed.
+	Unmodified,
+	/// The admin changed the file.
+	Modified,
+	/// The file was removed.
+	Missing,
+	/// dpkg hasn't recorded a hash yet, so it can't be compared.
+	Unknown,
+}
+
+/// A configuration file of an installed package.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub struct Conffile {
+	/// The path on the installed system. ex: `/etc/apt/apt.conf.d/01autoremove`
+	pub path: String,
+	/// The MD5 of the file when it was installed.
+	pub md5: String,
+	/// The file is no longer shipped by the package.
+	pub obsolete: bool,
+	/// The file will be removed on the next upgrade.
+	pub remove_on_upgrade: bool,
+	/// The root dir of the cache that the file was read from.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	root: PathBuf,
+}
+
+impl Conffile {
+	/// Parse one line of the Conffiles field.
+	///
+	/// ex: `/etc/foo.conf 9fb97a88cb7383934ef963352b53b4a7 obsolete`
+	fn parse(line: &str, root: &Path) -> Option<Conffile> {
+		let mut fields = line.split_whitespace();
+		let path = fields.next()?.to_string();
+		let md5 = fields.next()?.to_string();
+		let flags: Vec<&str> = fields.collect();
+
+		Some(Conffile {
+			path,
+			md5,
+			obsolete: flags.contains(&"obsolete"),
+			remove_on_upgrade: flags.contains(&"remove-on-upgrade"),
+			root: root.to_path_buf(),
+		})
+	}
+
+	/// Where the file is on disk.
+	///
+	/// This is under the root dir of the cache, see [`Cache::root_dir`].
+	pub fn disk_path(&self) -> PathBuf { root_path(&self.root, Path::new(&self.path)) }
+
+	/// Compare the file on disk with the recorded MD5.
+	pub fn state(&self) -> ConffileState {
+		// dpkg writes this before the file is hashed for the first time.
+		if self.md5 == "newconffile" {
+			return ConffileState::Unknown;
+		}
+
+		let path = self.disk_path();
+		if path.symlink_metadata().is_err() {
+			return ConffileState::Missing;
+		}
+
+		match hash_file(&path, "MD5Sum") {
+			Ok(md5) if md5 == self.md5 => ConffileState::Unmodified,
+			Ok(_) => ConffileState::Modified,
+			Err(_) => ConffileState::Unknown,
+		}
+	}
+}
+
+/// Read the Conffiles of the installed version from the dpkg status.
+pub(crate) fn conffiles(pkg: &Package) -> Vec<Conffile> {
+	match pkg.installed() {
+		Some(ver) => version_conffiles(&ver),
+		None => vec![],
+	}
+}
+
+/// Read the Conffiles of a version from the dpkg status.
+fn version_conffiles(ver: &Version) -> Vec<Conffile> {
+	// The status file is the only place that has the Conffiles field.
+	let Some(ver_file) = ver
+		.version_files()
+		.find(|ver_file| ver_file.package_file().archive() == Some("now"))
+	else {
+		return vec![];
+	};
+
+	let Some(field) = ver_file
+		.lookup()
+		.get_field(RecordField::Conffiles.to_string())
+	else {
+		return vec![];
+	};
+
+	let root = ver.cache.root_dir();
+	field
+		.lines()
+		.filter_map(|line| Conffile::parse(line, root))
+		.collect()
+}
+
+/// The answer to a conffile prompt of dpkg.
+#[cfg(feature = "deb")]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum ConffileAnswer {
+	/// Keep the file on disk. The new one is saved as `.dpkg-dist`.
+	Keep,
+	/// Install the new file. The old one is saved as `.dpkg-old`.
+	Replace,
+	/// Ask again with the diff from the file on disk to the new one.
+	ShowDiff,
+}
+
+/// Where the archive of a version is, if it has been downloaded.
+#[cfg(feature = "deb")]
+fn archive_path(ver: &Version) -> Option<PathBuf> {
+	// Local debs are read from where they are.
+	for pkg_file in ver.package_files() {
+		if let Some(filename) = pkg_file.filename() {
+			if filename.ends_with(".deb") && Path::new(filename).is_file() {
+				return Some(PathBuf::from(filename));
+			}
+		}
+	}
+
+	// This is the name apt gives the archives it downloads.
+	let quote = |string: &str, bad: &str| crate::util::raw::quote_string(string, bad.to_string());
+	let path = PathBuf::from(format!(
+		"{}{}_{}_{}.deb",
+		Config::new().dir("Dir::Cache::Archives", "/var/cache/apt/archives/"),
+		quote(ver.parent().name(), "_:"),
+		quote(ver.version(), "_:"),
+		quote(ver.arch(), "_:."),
+	));
+	path.is_file().then_some(path)
+}
+
+/// The conffiles that dpkg will ask about when `ver` is installed.
+///
+/// This follows dpkg. It asks when the file on disk and the new file were
+/// both changed from the hash in the status, and they aren't the same. Without
+/// a hash, like for a new install, it asks when there is already a file on
+/// disk that isn't the new one. Packages with only their conffiles left still
+/// have their hashes in the status.
+///
+/// The new files are extracted under `tmp`.
+#[cfg(feature = "deb")]
+fn conffile_prompts(
+	pkg: &Package,
+	ver: &Version,
+	tmp: &Path,
+) -> Result<Vec<ConffilePrompt>, AptErrors> {
+	// Without the archive there is nothing to compare with.
+	let Some(deb) = archive_path(ver) else {
+		return Ok(vec![]);
+	};
+	let deb = DebFile::open(deb)?;
+
+	let conffiles = deb.conffiles();
+	if conffiles.is_empty() {
+		return Ok(vec![]);
+	}
+
+	let recorded: Vec<Conffile> = pkg
+		.versions()
+		.map(|ver| version_conffiles(&ver))
+		.find(|conffiles| !conffiles.is_empty())
+		.unwrap_or_default();
+
+	let mut prompts = vec![];
+	for entry in deb.data_tar()?.entries()? {
+		let mut entry = entry?;
+		let path = entry.path()?.to_string_lossy().to_string();
+		let path = format!("/{}", path.trim_start_matches("./").trim_start_matches('/'));
+		if !conffiles.contains(&path) {
+			continue;
+		}
+
+		// dpkg doesn't bring back a conffile that was removed,
+		// and a missing file can't be in the way of a new one.
+		let current = root_path(pkg.cache.root_dir(), Path::new(&path));
+		let Ok(current_md5) = hash_file(&current, "MD5Sum") else {
+			continue;
+		};
+
+		let new = tmp.join(path.trim_start_matches('/'));
+		if let Some(parent) = new.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		entry.unpack(&new)?;
+		if !new.symlink_metadata()?.is_file() {
+			continue;
+		}
+
+		let new_md5 = hash_file(&new, "MD5Sum")?;
+		if new_md5 == current_md5 {
+			continue;
+		}
+
+		let old = recorded
+			.iter()
+			.find(|conffile| conffile.path == path && conffile.md5 != "newconffile");
+		let (user_edited, dist_edited) = match old {
+			Some(old) => (current_md5 != old.md5, new_md5 != old.md5),
+			None => (true, true),
+		};
+		if !(user_edited && dist_edited) {
+			continue;
+		}
+
+		prompts.push(ConffilePrompt {
+			current: current.display().to_string(),
+			new: new.display().to_string(),
+			user_edited,
+			dist_edited,
+		});
+	}
+	Ok(prompts)
+}
+
+/// The diff from the file on disk to the new conffile, like dpkg shows.
+#[cfg(feature = "deb")]
+fn conffile_diff(prompt: &ConffilePrompt) -> Result<String, AptErrors> {
+	let output = Command::new("diff")
+		.arg("-u")
+		.arg(&prompt.current)
+		.arg(&prompt.new)
+		.output()?;
+
+	// diff exits with 1 when the files are different.
+	if !matches!(output.status.code(), Some(0 | 1)) {
+		return Err(AptErrors::from(format!(
+			"diff failed: {}",
+			String::from_utf8_lossy(&output.stderr).trim()
+		)));
+	}
+	Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// The answers to the conffile prompts of an install.
+#[cfg(feature = "deb")]
+pub(crate) struct ConffileAnswers {
+	/// Each prompt, and true if the new conffile is installed.
+	answers: Vec<(ConffilePrompt, bool)>,
+	/// The conffiles that were replaced before dpkg runs.
+	replaced: Vec<PathBuf>,
+	/// Holds the new conffiles of the prompts.
+	_tmp: TempDir,
+}
+
+#[cfg(feature = "deb")]
+impl ConffileAnswers {
+	/// Get ready for dpkg to run, and return the option it needs.
+	///
+	/// If every answer is the same dpkg is told that answer. Otherwise the
+	/// conffiles answered with [`ConffileAnswer::Replace`] are replaced now,
+	/// and dpkg keeps the rest.
+	///
+	/// [`None`] if nothing was asked, so dpkg asks for itself.
+	pub(crate) fn prepare(&mut self) -> Result<Option<&'static str>, AptErrors> {
+		if self.answers.is_empty() {
+			return Ok(None);
+		}
+		if self.answers.iter().all(|(_, replace)| *replace) {
+			return Ok(Some("--force-confnew"));
+		}
+
+		let mut result = Ok(());
+		for (prompt, replace) in &self.answers {
+			if !replace {
+				continue;
+			}
+			if let Err(err) = replace_conffile(prompt) {
+				result = Err(err);
+				break;
+			}
+			self.replaced.push(PathBuf::from(&prompt.current));
+		}
+
+		if let Err(err) = result {
+			self.restore();
+			return Err(err);
+		}
+		Ok(Some("--force-confold"))
+	}
+
+	/// Put back the conffiles that were replaced, because dpkg failed.
+	pub(crate) fn restore(&mut self) {
+		for current in self.replaced.drain(..) {
+			let _ = fs::rename(with_suffix(&current, ".dpkg-old"), &current);
+		}
+	}
+}
+
+/// Ask `handler` about each conffile that dpkg would prompt for.
+///
+/// The archives must already be downloaded. Nothing is changed on disk until
+/// [`ConffileAnswers::prepare`].
+#[cfg(feature = "deb")]
+pub(crate) fn answer_conffiles(
+	cache: &Cache,
+	handler: &mut impl FnMut(&Package, &ConffilePrompt, Option<&str>) -> ConffileAnswer,
+) -> Result<ConffileAnswers, AptErrors> {
+	let tmp = TempDir::new("oma-apt-conffiles")?;
+	let mut answers = vec![];
+
+	for pkg in cache.packages(&PackageSort::default()) {
+		if !(pkg.marked_install()
+			|| pkg.marked_upgrade()
+			|| pkg.marked_downgrade()
+			|| pkg.marked_reinstall())
+		{
+			continue;
+		}
+		let Some(ver) = pkg.install_version() else {
+			continue;
+		};
+
+		for prompt in conffile_prompts(&pkg, &ver, tmp.path())? {
+			let mut diff = None;
+			let replace = loop {
+				match handler(&pkg, &prompt, diff.as_deref()) {
+					ConffileAnswer::Keep => break false,
+					ConffileAnswer::Replace => break true,
+					ConffileAnswer::ShowDiff => diff = Some(conffile_diff(&prompt)?),
+				}
+			};
+			answers.push((prompt, replace));
+		}
+	}
+
+	Ok(ConffileAnswers {
+		answers,
+		replaced: vec![],
+		_tmp: tmp,
+	})
+}
+
+/// The path with `suffix` added to the file name. ex: `/etc/foo.dpkg-old`
+#[cfg(feature = "deb")]
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+	let mut path = path.as_os_str().to_owned();
+	path.push(suffix);
+	PathBuf::from(path)
+}
+
+/// Put the new conffile in place of the one on disk.
+///
+/// The old file is kept as `.dpkg-old`, like dpkg does. The new file is
+/// copied next to it first and renamed over it, so the conffile is never
+/// missing or half written.
+#[cfg(feature = "deb")]
+fn replace_conffile(prompt: &ConffilePrompt) -> Result<(), AptErrors> {
+	let current = Path::new(&prompt.current);
+	let new = with_suffix(current, ".oma-apt-new");
+	let old = with_suffix(current, ".dpkg-old");
+	fs::copy(&prompt.new, &new)?;
+
+	let result = match fs::remove_file(&old) {
+		Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+		_ => Ok(()),
+	}
+	.and_then(|_| fs::hard_link(current, &old))
+	.and_then(|_| fs::rename(&new, current));
+
+	if result.is_err() {
+		let _ = fs::remove_file(&new);
+	}
+	Ok(result?)
+}
+
+/// The version that dpkg should be told about.
+///
+/// This is the installed version, or the newest if there isn't one.
+fn selection_version<'a>(pkg: &Package<'a>) -> Result<Version<'a>, AptErrors> {
+	pkg.installed()
+		.or_else(|| pkg.versions().next())
+		.ok_or_else(|| {
+			AptErrors::from(format!(
+				"Can't select versions from package '{}' as it is purely virtual",
+				pkg.fullname(true)
+			))
+		})
+}
+
+/// Get the selected state of every package like `dpkg --get-selections`.
+///
+/// Packages with an [`PkgSelectedState::Unknown`] state are skipped.
+pub(crate) fn get_selections(cache: &Cache) -> Vec<Selection> {
+	cache
+		.packages(&PackageSort::default().names())
+		.filter_map(|pkg| {
+			let state = pkg.selected_state();
+			if state == PkgSelectedState::Unknown {
+				return None;
+			}
+			Some(Selection {
+				package: pkg.fullname(true),
+				state,
+			})
+		})
+		.collect()
+}
+
+/// Write all selections at once like `dpkg --set-selections`.
+///
+/// Nothing is written if any of the packages can't be found.
+pub(crate) fn set_selections<'a>(
+	selections: impl IntoIterator<Item = (Package<'a>, PkgSelectedState)>,
+) -> Result<(), AptErrors> {
+	let changes = raw::create_state_changes();
+
+	for (pkg, state) in selections {
+		let ver = selection_version(&pkg)?;
+		match state {
+			PkgSelectedState::Install => {
+				// Install on a held package means unhold
+				if pkg.selected_state() == PkgSelectedState::Hold {
+					changes.unhold(&ver)
+				} else {
+					changes.install(&ver)
+				}
+			},
+			PkgSelectedState::Hold => changes.hold(&ver),
+			PkgSelectedState::DeInstall => changes.remove(&ver),
+			PkgSelectedState::Purge => changes.purge(&ver),
+			PkgSelectedState::Unknown => {
+				return Err(AptErrors::from(format!(
+					"Can't set the selection of '{}' to unknown",
+					pkg.fullname(true)
+				)));
+			},
+		}
+	}
+
+	if changes.empty() {
+		return Ok(());
+	}
+	Ok(changes.save()?)
+}
+
+#[cxx::bridge]
+pub(crate) mod raw {
+	unsafe extern "C++" {
+		include!("oma-apt/apt-pkg-c/dpkg.h");
+
+		/// Selection changes that are written with `dpkg --set-selections`.
+		type StateChanges;
+		type VerIterator = crate::raw::VerIterator;
+
+		/// Create an empty set of selection changes.
+		pub fn create_state_changes() -> UniquePtr<StateChanges>;
+
+		/// Set the package of the version to hold.
+		pub fn hold(self: &StateChanges, ver: &VerIterator);
+
+		/// Set the package of the version from hold back to install.
+		pub fn unhold(self: &StateChanges, ver: &VerIterator);
+
+		/// Set the package of the version to install.
+		pub fn install(self: &StateChanges, ver: &VerIterator);
+
+		/// Set the package of the version to deinstall.
+		pub fn remove(self: &StateChanges, ver: &VerIterator);
+
+		/// Set the package of the version to purge.
+		pub fn purge(self: &StateChanges, ver: &VerIterator);
+
+		/// True if there are no changes.
+		pub fn empty(self: &StateChanges) -> bool;
+
+		/// Forget every change.
+		pub fn clear(self: &StateChanges);
+
+		/// Run `dpkg --set-selections` with the changes.
+		pub fn save(self: &StateChanges) -> Result<()>;
+	}
+}
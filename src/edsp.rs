@@ -0,0 +1,141 @@
+//! Use an external dependency solver through EDSP.
+//!
+//! The External Dependency Solver Protocol is how apt talks to solvers such as
+//! `apt-cudf` or its own `apt-internal-solver`. A request is the current
+//! marks on the depcache followed by the scenario, which is every package the
+//! solver is allowed to pick from. The answer from the solver is marked back
+//! onto the depcache.
+//!
+//! Requests can be written to a file, so a resolver problem can be replayed
+//! later with any solver.
+use std::fs::File;
+use std::os::fd::IntoRawFd;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::error::AptErrors;
+use crate::progress::OperationProgress;
+use crate::Cache;
+
+/// Request flags defined in edsp.h
+#[allow(non_upper_case_globals, non_snake_case)]
+pub mod RequestFlags {
+	/// Ask the solver to remove packages that are no longer needed.
+	pub const Autoremove: u32 = 1;
+	/// Ask the solver to upgrade every installed package.
+	pub const UpgradeAll: u32 = 2;
+	/// Don't let the solver install new packages.
+	pub const ForbidNewInstall: u32 = 4;
+	/// Don't let the solver remove packages.
+	pub const ForbidRemove: u32 = 8;
+}
+
+/// Write the request and scenario to `path`.
+pub(crate) fn write_request(
+	cache: &Cache,
+	path: &Path,
+	flags: u32,
+	progress: &mut OperationProgress,
+) -> Result<(), AptErrors> {
+	let file = File::create(path)?;
+	Ok(raw::write_request(
+		cache.depcache(),
+		file.into_raw_fd(),
+		flags,
+		progress.pin(),
+	)?)
+}
+
+/// Mark the answer in `path` onto the depcache.
+pub(crate) fn read_response(
+	cache: &Cache,
+	path: &Path,
+	progress: &mut OperationProgress,
+) -> Result<(), AptErrors> {
+	let file = File::open(path)?;
+	Ok(raw::read_response(
+		cache.depcache(),
+		file.into_raw_fd(),
+		progress.pin(),
+	)?)
+}
+
+/// Mark the request at the start of `path` onto the depcache.
+pub(crate) fn read_request(cache: &Cache, path: &Path) -> Result<(), AptErrors> {
+	let file = File::open(path)?;
+	Ok(raw::read_request(cache.depcache(), file.into_raw_fd())?)
+}
+
+/// Send the request to the solver on its stdin
+/// and mark the answer it writes to stdout.
+pub(crate) fn solve(
+	cache: &Cache,
+	solver: &mut Command,
+	flags: u32,
+	progress: &mut OperationProgress,
+) -> Result<(), AptErrors> {
+	let name = solver.get_program().to_string_lossy().to_string();
+	let mut child = solver
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.spawn()
+		.map_err(|err| AptErrors::from(format!("Unable to run the solver '{name}': {err}")))?;
+
+	// The C++ side takes ownership of the pipes and closes them when it's done.
+	let stdin = child.stdin.take().unwrap().into_raw_fd();
+	let stdout = child.stdout.take().unwrap();
+
+	let answered = match raw::write_request(cache.depcache(), stdin, flags, progress.pin()) {
+		Ok(_) => raw::read_response(cache.depcache(), stdout.into_raw_fd(), progress.pin()),
+		Err(err) => {
+			// Don't let the solver block on a full pipe while we wait for it.
+			drop(stdout);
+			Err(err)
+		},
+	};
+
+	let status = child.wait()?;
+	answered?;
+
+	if !status.success() {
+		return Err(AptErrors::from(format!(
+			"The solver '{name}' failed with {status}"
+		)));
+	}
+	Ok(())
+}
+
+#[cxx::bridge]
+pub(crate) mod raw {
+	unsafe extern "C++" {
+		include!("oma-apt/apt-pkg-c/edsp.h");
+
+		type PkgDepCache = crate::depcache::raw::PkgDepCache;
+		type OperationProgress<'a> = crate::progress::OperationProgress<'a>;
+
+		/// Write an EDSP request followed by the scenario.
+		///
+		/// The file descriptor is closed when this returns.
+		pub fn write_request(
+			cache: &PkgDepCache,
+			fd: i32,
+			flags: u32,
+			op_progress: Pin<&mut OperationProgress>,
+		) -> Result<()>;
+
+		/// Read the install and remove lines of an EDSP request
+		/// and mark them on the depcache.
+		///
+		/// The file descriptor is closed when this returns.
+		pub fn read_request(cache: &PkgDepCache, fd: i32) -> Result<()>;
+
+		/// Read an EDSP answer and mark it on the depcache.
+		///
+		/// The file descriptor is closed when this returns.
+		pub fn read_response(
+			cache: &PkgDepCache,
+			fd: i32,
+			op_progress: Pin<&mut OperationProgress>,
+		) -> Result<()>;
+	}
+}
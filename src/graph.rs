@@ -0,0 +1,449 @@
+//! Walk dependencies or reverse dependencies recursively.
+//!
+//! This is similar to `apt-cache depends --recurse`,
+//! `apt-cache rdepends --recurse` and `debtree`.
+//!
+//! The graph only holds package names, so it does not borrow the cache and
+//! can be serialized with the `serde` feature or exported to Graphviz DOT.
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{DepType, Package, Version};
+
+/// Which way to walk the dependencies.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Direction {
+	/// Follow what each package depends on.
+	Forward,
+	/// Follow what depends on each package.
+	Reverse,
+}
+
+/// Determines how a [`DependencyGraph`] is built.
+pub struct GraphOptions {
+	direction: Direction,
+	dep_types: Vec<DepType>,
+	installed_only: bool,
+	providers: bool,
+	max_depth: Option<usize>,
+}
+
+impl Default for GraphOptions {
+	fn default() -> GraphOptions {
+		GraphOptions {
+			direction: Direction::Forward,
+			dep_types: vec![DepType::Depends, DepType::PreDepends],
+			installed_only: false,
+			providers: false,
+			max_depth: None,
+		}
+	}
+}
+
+impl GraphOptions {
+	/// Walk reverse dependencies instead of dependencies.
+	pub fn reverse(mut self) -> Self {
+		self.direction = Direction::Reverse;
+		self
+	}
+
+	/// Only follow these dependency types.
+	///
+	/// The default is Depends and PreDepends.
+	pub fn dep_types(mut self, dep_types: &[DepType]) -> Self {
+		self.dep_types = dep_types.to_vec();
+		self
+	}
+
+	/// Only include packages that are installed.
+	///
+	/// Dependencies are taken from the installed version,
+	/// and reverse dependencies must come from an installed version.
+	pub fn installed_only(mut self) -> Self {
+		self.installed_only = true;
+		self
+	}
+
+	/// Expand virtual packages into the packages that provide them.
+	pub fn expand_providers(mut self) -> Self {
+		self.providers = true;
+		self
+	}
+
+	/// Stop walking after this many levels. The root package is depth 0.
+	pub fn max_depth(mut self, depth: usize) -> Self {
+		self.max_depth = Some(depth);
+		self
+	}
+}
+
+/// A single edge in the graph.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Edge {
+	/// The package at the other end of the edge.
+	pub target: String,
+	/// The type of the dependency.
+	pub dep_type: DepType,
+	/// The virtual package name, if the edge was found through a provider.
+	pub via: Option<String>,
+}
+
+/// A package in the graph along with its outgoing edges.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone)]
+pub struct Node {
+	/// The full name of the package.
+	pub name: String,
+	/// How many levels away from the root this package was first found.
+	pub depth: usize,
+	/// For [`Direction::Forward`] these are the packages this one depends on.
+	///
+	/// For [`Direction::Reverse`] these are the packages that depend on it.
+	pub edges: Vec<Edge>,
+}
+
+/// A recursive graph of dependencies starting at one package.
+///
+/// # Example:
+/// ```
+/// use oma_apt::graph::{DependencyGraph, GraphOptions};
+/// use oma_apt::new_cache;
+///
+/// let cache = new_cache!().unwrap();
+/// let pkg = cache.get("apt").unwrap();
+///
+/// let graph = DependencyGraph::new(&pkg, &GraphOptions::default().reverse().max_depth(2));
+/// for node in graph.nodes() {
+///     println!("{} is {} levels away", node.name, node.depth);
+/// }
+///
+/// println!("{}", graph.to_dot());
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone)]
+pub struct DependencyGraph {
+	direction: Direction,
+	nodes: Vec<Node>,
+}
+
+impl DependencyGraph {
+	/// Build the graph starting from `root`.
+	pub fn new(root: &Package, options: &GraphOptions) -> DependencyGraph {
+		let mut nodes: Vec<Node> = vec![];
+		let mut seen: HashMap<String, usize> = HashMap::new();
+		let mut queue = VecDeque::new();
+
+		seen.insert(root.fullname(true), 0);
+		nodes.push(Node {
+			name: root.fullname(true),
+			depth: 0,
+			edges: vec![],
+		});
+		queue.push_back((root.clone(), 0));
+
+		while let Some((pkg, depth)) = queue.pop_front() {
+			if options.max_depth.is_some_and(|max| depth >= max) {
+				continue;
+			}
+
+			let mut edges = vec![];
+			let neighbours = match options.direction {
+				Direction::Forward => forward(&pkg, options),
+				Direction::Reverse => reverse(&pkg, options),
+			};
+
+			for (target, dep_type, via) in neighbours {
+				let name = target.fullname(true);
+				let edge = Edge {
+					target: name.clone(),
+					dep_type,
+					via,
+				};
+
+				// The same package can be reached more than once through
+				// multiple versions or or groups.
+				if edges.contains(&edge) {
+					continue;
+				}
+				edges.push(edge);
+
+				if !seen.contains_key(&name) {
+					seen.insert(name.clone(), nodes.len());
+					nodes.push(Node {
+						name,
+						depth: depth + 1,
+						edges: vec![],
+					});
+					queue.push_back((target, depth + 1));
+				}
+			}
+
+			let index = seen[&pkg.fullname(true)];
+			nodes[index].edges = edges;
+		}
+
+		DependencyGraph {
+			direction: options.direction,
+			nodes,
+		}
+	}
+
+	/// The direction the graph was walked in.
+	pub fn direction(&self) -> Direction { self.direction }
+
+	/// The root package of the graph.
+	pub fn root(&self) -> &Node { &self.nodes[0] }
+
+	/// All packages in the graph in the order they were found.
+	pub fn nodes(&self) -> &[Node] { &self.nodes }
+
+	/// Get a package in the graph by its full name.
+	pub fn get(&self, name: &str) -> Option<&Node> { self.nodes.iter().find(|n| n.name == name) }
+
+	/// Returns every dependency cycle in the graph.
+	///
+	/// Each cycle is a list of packages that can all reach each other.
+	pub fn cycles(&self) -> Vec<Vec<String>> {
+		let index: HashMap<&str, usize> = self
+			.nodes
+			.iter()
+			.enumerate()
+			.map(|(i, n)| (n.name.as_str(), i))
+			.collect();
+
+		let mut tarjan = Tarjan {
+			graph: self,
+			index: &index,
+			counter: 0,
+			indices: vec![None; self.nodes.len()],
+			lowlink: vec![0; self.nodes.len()],
+			stack: vec![],
+			on_stack: vec![false; self.nodes.len()],
+			cycles: vec![],
+		};
+
+		for node in 0..self.nodes.len() {
+			if tarjan.indices[node].is_none() {
+				tarjan.connect(node);
+			}
+		}
+		tarjan.cycles
+	}
+
+	/// Export the graph in Graphviz DOT format.
+	///
+	/// Arrows always point from a package to what it depends on,
+	/// regardless of the direction the graph was walked in.
+	pub fn to_dot(&self) -> String {
+		let mut dot = format!("digraph \"{}\" {{\n", self.root().name);
+
+		for node in &self.nodes {
+			for edge in &node.edges {
+				let (from, to) = match self.direction {
+					Direction::Forward => (&node.name, &edge.target),
+					Direction::Reverse => (&edge.target, &node.name),
+				};
+
+				let mut label = edge.dep_type.to_string();
+				if let Some(via) = &edge.via {
+					write!(label, " ({via})").unwrap();
+				}
+
+				let style = match edge.dep_type {
+					DepType::Depends | DepType::PreDepends => "solid",
+					DepType::Recommends => "dashed",
+					_ => "dotted",
+				};
+
+				writeln!(
+					dot,
+					"\t\"{from}\" -> \"{to}\" [label=\"{label}\", style={style}];"
+				)
+				.unwrap();
+			}
+		}
+
+		dot.push_str("}\n");
+		dot
+	}
+}
+
+/// The version that dependencies should be read from.
+fn graph_version<'a>(pkg: &Package<'a>, options: &GraphOptions) -> Option<Version<'a>> {
+	if options.installed_only {
+		return pkg.installed();
+	}
+	pkg.candidate().or_else(|| pkg.installed())
+}
+
+/// Packages that `pkg` depends on.
+fn forward<'a>(
+	pkg: &Package<'a>,
+	options: &GraphOptions,
+) -> Vec<(Package<'a>, DepType, Option<String>)> {
+	let mut found = vec![];
+	let Some(ver) = graph_version(pkg, options) else {
+		return found;
+	};
+
+	for dep_type in &options.dep_types {
+		let Some(deps) = ver.get_depends(dep_type) else {
+			continue;
+		};
+
+		for base_dep in deps.iter().flat_map(|dep| dep.iter()) {
+			let target = base_dep.target_package();
+
+			if options.providers && !target.has_versions() {
+				for provider in target.provides() {
+					let provider = provider.package();
+					if options.installed_only && !provider.is_installed() {
+						continue;
+					}
+					found.push((provider, dep_type.clone(), Some(target.fullname(true))));
+				}
+				continue;
+			}
+
+			if options.installed_only && !target.is_installed() {
+				continue;
+			}
+			found.push((target.clone(), dep_type.clone(), None));
+		}
+	}
+	found
+}
+
+/// Packages that depend on `pkg`.
+fn reverse<'a>(
+	pkg: &Package<'a>,
+	options: &GraphOptions,
+) -> Vec<(Package<'a>, DepType, Option<String>)> {
+	let mut found = vec![];
+	let mut targets = vec![(pkg.clone(), None)];
+
+	// Packages can also be depended on through the names they provide.
+	if options.providers {
+		if let Some(ver) = graph_version(pkg, options) {
+			for provider in ver.provides() {
+				if let Some(virt) = pkg.cache.get(provider.name()) {
+					let name = virt.fullname(true);
+					targets.push((virt, Some(name)));
+				}
+			}
+		}
+	}
+
+	for (target, via) in targets {
+		for dep_type in &options.dep_types {
+			let Some(deps) = target.rdepends().get(dep_type) else {
+				continue;
+			};
+
+			for base_dep in deps.iter().flat_map(|dep| dep.iter()) {
+				// Skip dependencies from versions that aren't installed.
+				if options.installed_only && unsafe { !base_dep.parent_ver().is_installed() } {
+					continue;
+				}
+				found.push((
+					base_dep.target_package().clone(),
+					dep_type.clone(),
+					via.clone(),
+				));
+			}
+		}
+	}
+	found
+}
+
+/// Tarjan's strongly connected components, used to find cycles.
+struct Tarjan<'g> {
+	graph: &'g DependencyGraph,
+	index: &'g HashMap<&'g str, usize>,
+	counter: usize,
+	indices: Vec<Option<usize>>,
+	lowlink: Vec<usize>,
+	stack: Vec<usize>,
+	on_stack: Vec<bool>,
+	cycles: Vec<Vec<String>>,
+}
+
+impl Tarjan<'_> {
+	/// Find the components reachable from `root`.
+	///
+	/// Dependency chains can be long, so this keeps its own stack of
+	/// `(node, next edge, has a self loop)` instead of recursing.
+	fn connect(&mut self, root: usize) {
+		let graph = self.graph;
+		self.visit(root);
+		let mut calls = vec![(root, 0, false)];
+
+		while let Some((node, next, self_loop)) = calls.last_mut() {
+			let node = *node;
+
+			if let Some(edge) = graph.nodes[node].edges.get(*next) {
+				*next += 1;
+				let target = self.index[edge.target.as_str()];
+				*self_loop |= target == node;
+
+				match self.indices[target] {
+					None => {
+						self.visit(target);
+						calls.push((target, 0, false));
+					},
+					Some(index) if self.on_stack[target] => {
+						self.lowlink[node] = self.lowlink[node].min(index);
+					},
+					Some(_) => {},
+				}
+				continue;
+			}
+
+			// Every edge is done, so return to the caller.
+			let self_loop = *self_loop;
+			calls.pop();
+			if let Some(&(parent, ..)) = calls.last() {
+				self.lowlink[parent] = self.lowlink[parent].min(self.lowlink[node]);
+			}
+			self.component(node, self_loop);
+		}
+	}
+
+	fn visit(&mut self, node: usize) {
+		self.indices[node] = Some(self.counter);
+		self.lowlink[node] = self.counter;
+		self.counter += 1;
+		self.stack.push(node);
+		self.on_stack[node] = true;
+	}
+
+	/// Pop the component of `node` if it is the root of one.
+	fn component(&mut self, node: usize, self_loop: bool) {
+		if Some(self.lowlink[node]) != self.indices[node] {
+			return;
+		}
+
+		let mut component = vec![];
+		while let Some(member) = self.stack.pop() {
+			self.on_stack[member] = false;
+			component.push(member);
+			if member == node {
+				break;
+			}
+		}
+
+		if component.len() > 1 || self_loop {
+			let mut cycle: Vec<String> = component
+				.into_iter()
+				.map(|i| self.graph.nodes[i].name.clone())
+				.collect();
+			cycle.sort();
+			self.cycles.push(cycle);
+		}
+	}
+}
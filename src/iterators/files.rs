@@ -2,7 +2,7 @@ use std::cell::OnceCell;
 
 use cxx::UniquePtr;
 
-use crate::raw::{IndexFile, PkgFileIterator, VerFileIterator};
+use crate::raw::{DescIterator, IndexFile, PkgFileIterator, VerFileIterator};
 use crate::{Cache, PackageRecords};
 
 /// Associates a version with a PackageFile
@@ -27,6 +27,34 @@ impl<'a> VersionFile<'a> {
 	}
 }
 
+/// A single Description of a Version.
+///
+/// Versions have one Description for each translation that is available.
+pub struct Description<'a> {
+	pub(crate) ptr: UniquePtr<DescIterator>,
+	cache: &'a Cache,
+}
+
+impl<'a> Description<'a> {
+	pub fn new(ptr: UniquePtr<DescIterator>, cache: &'a Cache) -> Description<'a> {
+		Description { ptr, cache }
+	}
+
+	/// The language code of the Description. ex: de, pt_BR
+	///
+	/// This is empty for the untranslated Description from the Packages file.
+	pub fn language(&self) -> &str { self.ptr.language_code() }
+
+	/// Return the PkgRecords Parser for the Description
+	pub fn lookup(&self) -> &PackageRecords { self.cache.records().desc_lookup(&self.ptr) }
+
+	/// Get the short description in this language
+	pub fn summary(&self) -> Option<String> { self.lookup().short_desc() }
+
+	/// Get the long description in this language
+	pub fn description(&self) -> Option<String> { self.lookup().long_desc() }
+}
+
 /// Stores information about the files used to generate the cache
 ///
 /// Package files are referenced by Version structures to be able to know
@@ -164,6 +192,12 @@ pub(crate) mod raw {
 		pub fn raw_next(self: Pin<&mut VerFileIterator>);
 		pub fn end(self: &VerFileIterator) -> bool;
 
+		/// The language code of the Description. Empty if untranslated.
+		pub fn language_code(self: &DescIterator) -> &str;
+
+		/// The MD5 of the untranslated long description.
+		pub fn md5(self: &DescIterator) -> Result<&str>;
+
 		#[cxx_name = "Index"]
 		pub fn index(self: &DescIterator) -> u64;
 		/// Clone the pointer.
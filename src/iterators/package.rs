@@ -2,14 +2,22 @@ use std::cell::OnceCell;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
 
 use cxx::UniquePtr;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
+use crate::dpkg::{Conffile, ConffileState, ModifiedFile};
+use crate::error::AptErrors;
 use crate::raw::{IntoRawIter, PkgIterator};
-use crate::{create_depends_map, util, Cache, DepType, Dependency, Provider, Version};
+use crate::why::{self, WhyChain, WhyNot};
+use crate::{create_depends_map, dpkg, util, Cache, DepType, Dependency, Provider, Version};
 
 /// The state that the user wishes the package to be in.
-#[derive(Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
 pub enum PkgSelectedState {
 	Unknown = 0,
 	Install = 1,
@@ -31,6 +39,38 @@ impl From<u8> for PkgSelectedState {
 	}
 }
 
+impl PkgSelectedState {
+	/// The name dpkg uses for the state in `dpkg --get-selections`.
+	pub fn to_str(&self) -> &'static str {
+		match self {
+			PkgSelectedState::Unknown => "unknown",
+			PkgSelectedState::Install => "install",
+			PkgSelectedState::Hold => "hold",
+			PkgSelectedState::DeInstall => "deinstall",
+			PkgSelectedState::Purge => "purge",
+		}
+	}
+}
+
+impl FromStr for PkgSelectedState {
+	type Err = AptErrors;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s {
+			"unknown" => PkgSelectedState::Unknown,
+			"install" => PkgSelectedState::Install,
+			"hold" => PkgSelectedState::Hold,
+			"deinstall" => PkgSelectedState::DeInstall,
+			"purge" => PkgSelectedState::Purge,
+			_ => return Err(AptErrors::from(format!("Unknown selection '{s}'"))),
+		})
+	}
+}
+
+impl fmt::Display for PkgSelectedState {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.to_str()) }
+}
+
 /// Installation state of the package
 #[derive(Debug, Eq, PartialEq, Hash)]
 pub enum PkgInstState {
@@ -171,6 +211,30 @@ impl<'a> Package<'a> {
 	/// True if the package provides any other packages.
 	pub fn has_provides(&self) -> bool { unsafe { !self.ptr.provides().end() } }
 
+	/// True if the package is for the native architecture of the cache.
+	/// See [`Cache::native_arch`].
+	///
+	/// `Architecture: all` packages are native.
+	pub fn is_native(&self) -> bool { self.arch() == self.cache.native_arch() }
+
+	/// True if the package is for an architecture other than the native one.
+	pub fn is_foreign(&self) -> bool { !self.is_native() }
+
+	/// True if the package is `Architecture: all`.
+	///
+	/// This looks at the candidate, then the installed version.
+	pub fn is_arch_all(&self) -> bool {
+		self.candidate()
+			.or_else(|| self.installed())
+			.or_else(|| self.versions().next())
+			.is_some_and(|ver| ver.multi_arch().is_all())
+	}
+
+	/// The package for every architecture that has this name.
+	///
+	/// This includes the package itself.
+	pub fn group(&self) -> Vec<Package<'a>> { self.cache.group(self.name()) }
+
 	/// The installed state of this package.
 	pub fn inst_state(&self) -> PkgInstState { PkgInstState::from(self.ptr.inst_state()) }
 
@@ -244,6 +308,98 @@ impl<'a> Package<'a> {
 	/// Check if the package is auto installed. (Not installed by the user)
 	pub fn is_auto_installed(&self) -> bool { self.cache.depcache().is_auto_installed(self) }
 
+	/// The conffiles of the installed version, read from the dpkg status.
+	///
+	/// This is empty if the package isn't installed.
+	///
+	/// # Example:
+	/// ```
+	/// use oma_apt::dpkg::ConffileState;
+	/// use oma_apt::new_cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// for conffile in cache.get("apt").unwrap().conffiles() {
+	///     if conffile.state() != ConffileState::Unmodified {
+	///         println!("{} was changed", conffile.path);
+	///     }
+	/// }
+	/// ```
+	pub fn conffiles(&self) -> Vec<Conffile> { dpkg::conffiles(self) }
+
+	/// The conffiles that the admin has modified or removed.
+	///
+	/// Obsolete conffiles are not included.
+	pub fn changed_conffiles(&self) -> Vec<Conffile> {
+		self.conffiles()
+			.into_iter()
+			.filter(|conffile| {
+				!conffile.obsolete
+					&& matches!(
+						conffile.state(),
+						ConffileState::Modified | ConffileState::Missing
+					)
+			})
+			.collect()
+	}
+
+	/// The files that dpkg installed for the package, like `dpkg -L`.
+	///
+	/// This is empty if the package isn't installed.
+	pub fn installed_files(&self) -> Result<Vec<PathBuf>, AptErrors> { dpkg::installed_files(self) }
+
+	/// Check the installed files against the md5sums of the package,
+	/// like `dpkg --verify` or `debsums`.
+	///
+	/// Only the files that don't match are returned.
+	pub fn verify_files(&self) -> Result<Vec<ModifiedFile>, AptErrors> { dpkg::verify(self) }
+
+	/// Set the package to hold like `apt-mark hold`.
+	///
+	/// This is written to dpkg right away.
+	/// The cache must be reopened to see the new selected state.
+	pub fn hold(&self) -> Result<(), AptErrors> { self.set_selection(PkgSelectedState::Hold) }
+
+	/// Take the package off hold like `apt-mark unhold`.
+	pub fn unhold(&self) -> Result<(), AptErrors> {
+		if self.selected_state() != PkgSelectedState::Hold {
+			return Ok(());
+		}
+		self.set_selection(PkgSelectedState::Install)
+	}
+
+	/// Set the dpkg selection of the package like `dpkg --set-selections`.
+	///
+	/// This is written to dpkg right away.
+	/// The cache must be reopened to see the new selected state.
+	pub fn set_selection(&self, state: PkgSelectedState) -> Result<(), AptErrors> {
+		dpkg::set_selections([(self.clone(), state)])
+	}
+
+	/// Explain why the package is installed like `aptitude why`.
+	///
+	/// Returns the shortest chain of installed Depends and Recommends from
+	/// each manually installed package that holds this one on the system.
+	/// Chains made only of Depends come first, then shorter chains.
+	///
+	/// # Example:
+	/// ```
+	/// use oma_apt::new_cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// let pkg = cache.get("libapt-pkg6.0").unwrap();
+	///
+	/// if let Some(chain) = pkg.why().first() {
+	///     print!("{chain}");
+	/// }
+	/// ```
+	pub fn why(&self) -> Vec<WhyChain> { why::why(self) }
+
+	/// Explain why the candidate can not be installed like `aptitude why-not`.
+	///
+	/// Returns every installed package that Conflicts with or Breaks the
+	/// candidate, from either side of the relationship.
+	pub fn why_not(&self) -> Vec<WhyNot> { why::why_not(self) }
+
 	/// Check if the package is auto removable
 	pub fn is_auto_removable(&self) -> bool {
 		(self.is_installed() || self.marked_install()) && self.cache.depcache().is_garbage(self)
@@ -410,6 +566,13 @@ impl<'a> Package<'a> {
 	/// for when [`crate::cache::Cache::resolve`] is called.
 	pub fn protect(&self) { self.cache.resolver().protect(self) }
 
+	/// Tell the resolver that the package is to be removed
+	/// for when [`crate::cache::Cache::resolve`] is called.
+	///
+	/// Like `apt-get remove`, use this after [`Package::mark_delete`] and
+	/// [`Package::protect`] so the removal isn't undone.
+	pub fn resolver_remove(&self) { self.cache.resolver().remove(self) }
+
 	pub fn changelog_uri(&self) -> Option<String> {
 		let cand = self.candidate()?;
 
@@ -563,6 +726,19 @@ pub(crate) mod raw {
 		/// The returned UniquePtr cannot outlive the cache.
 		unsafe fn rdepends(self: &PkgIterator) -> UniquePtr<DepIterator>;
 
+		/// The next package in the group, which is the same name
+		/// for another architecture.
+		///
+		/// # Safety
+		///
+		/// If the inner pointer is null segfaults can occur.
+		///
+		/// Using [`crate::raw::IntoRawIter::make_safe`] to convert to an Option
+		/// is recommended.
+		///
+		/// The returned UniquePtr cannot outlive the cache.
+		unsafe fn next_in_group(self: &PkgIterator) -> UniquePtr<PkgIterator>;
+
 		#[cxx_name = "Index"]
 		pub fn index(self: &PkgIterator) -> u64;
 		/// Clone the pointer.
@@ -4,18 +4,135 @@ use std::collections::HashMap;
 use std::fmt;
 
 use cxx::UniquePtr;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
 use crate::raw::{IntoRawIter, VerIterator};
 use crate::util::cmp_versions;
 use crate::{
-	create_depends_map, Cache, DepType, Dependency, Package, PackageFile, PackageRecords, Provider,
-	VersionFile,
+	create_depends_map, Cache, DepType, Dependency, Description, Package, PackageFile,
+	PackageRecords, Provider, VersionFile,
 };
 
+/// The Multi-Arch field of a version.
+///
+/// `Architecture: all` is stored as a flag alongside the field,
+/// which is why there are combined variants.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum MultiArch {
+	/// No Multi-Arch field.
+	None = 0,
+	/// `Architecture: all` without a Multi-Arch field.
+	All = 1,
+	/// `Multi-Arch: foreign`
+	Foreign = 2,
+	/// `Architecture: all` with `Multi-Arch: foreign`
+	AllForeign = 3,
+	/// `Multi-Arch: same`
+	Same = 4,
+	/// `Multi-Arch: allowed`
+	Allowed = 8,
+	/// `Architecture: all` with `Multi-Arch: allowed`
+	AllAllowed = 9,
+}
+
+impl From<u8> for MultiArch {
+	fn from(value: u8) -> Self {
+		match value {
+			1 => MultiArch::All,
+			2 => MultiArch::Foreign,
+			3 => MultiArch::AllForeign,
+			4 => MultiArch::Same,
+			8 => MultiArch::Allowed,
+			9 => MultiArch::AllAllowed,
+			_ => MultiArch::None,
+		}
+	}
+}
+
+impl MultiArch {
+	/// True if the version is `Architecture: all`.
+	pub fn is_all(&self) -> bool { (*self as u8) & (MultiArch::All as u8) != 0 }
+
+	/// True if the version can satisfy dependencies from other architectures.
+	pub fn is_foreign(&self) -> bool { (*self as u8) & (MultiArch::Foreign as u8) != 0 }
+
+	/// True if the version can be satisfied by `:any` dependencies.
+	pub fn is_allowed(&self) -> bool { (*self as u8) & (MultiArch::Allowed as u8) != 0 }
+
+	/// True if the version can be installed for many architectures at once.
+	pub fn is_same(&self) -> bool { *self == MultiArch::Same }
+
+	/// Decide if a dependency is satisfied by a version on another
+	/// architecture.
+	///
+	/// * `arch` is the architecture of the version that might satisfy it.
+	/// * `from_arch` is the architecture of the package with the dependency.
+	/// * `native` is the architecture of the system, or the build architecture
+	///   when cross building.
+	///
+	/// `Architecture: all` versions are treated as `native`.
+	pub fn satisfies(
+		&self,
+		qualifier: &ArchQualifier,
+		arch: &str,
+		from_arch: &str,
+		native: &str,
+	) -> bool {
+		let arch = if self.is_all() || arch == "all" { native } else { arch };
+		let from_arch = if from_arch == "all" { native } else { from_arch };
+
+		match qualifier {
+			ArchQualifier::None => arch == from_arch || self.is_foreign(),
+			ArchQualifier::Any => self.is_allowed(),
+			ArchQualifier::Native => arch == native,
+			ArchQualifier::Arch(wanted) => arch == wanted,
+		}
+	}
+}
+
+/// The architecture qualifier of a dependency. ex: `python3:any`
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+pub enum ArchQualifier {
+	/// No qualifier, the dependency is for the same architecture.
+	None,
+	/// `:any` is satisfied by `Multi-Arch: allowed` on any architecture.
+	Any,
+	/// `:native` is satisfied by the native architecture.
+	Native,
+	/// A specific architecture like `:i386`.
+	Arch(String),
+}
+
+impl ArchQualifier {
+	/// Split a dependency name into the package name and the qualifier.
+	///
+	/// ```
+	/// use oma_apt::ArchQualifier;
+	///
+	/// assert_eq!(ArchQualifier::split("python3:any"), ("python3", ArchQualifier::Any));
+	/// assert_eq!(ArchQualifier::split("gcc"), ("gcc", ArchQualifier::None));
+	/// ```
+	pub fn split(name: &str) -> (&str, ArchQualifier) {
+		let Some((name, arch)) = name.split_once(':') else {
+			return (name, ArchQualifier::None);
+		};
+
+		let qualifier = match arch {
+			"any" => ArchQualifier::Any,
+			"native" => ArchQualifier::Native,
+			_ => ArchQualifier::Arch(arch.to_string()),
+		};
+		(name, qualifier)
+	}
+}
+
 /// Represents a single Version of a package.
 pub struct Version<'a> {
 	pub(crate) ptr: UniquePtr<VerIterator>,
-	cache: &'a Cache,
+	pub(crate) cache: &'a Cache,
 	depends_map: OnceCell<HashMap<DepType, Vec<Dependency<'a>>>>,
 }
 
@@ -59,6 +176,20 @@ impl<'a> Version<'a> {
 	/// Return the version's parent package.
 	pub fn parent(&self) -> Package<'a> { Package::new(self.cache, unsafe { self.parent_pkg() }) }
 
+	/// The Multi-Arch field of the version.
+	pub fn multi_arch(&self) -> MultiArch { MultiArch::from(self.ptr.multi_arch()) }
+
+	/// True if a dependency from a package on `from_arch` would be satisfied
+	/// by this version. See [`MultiArch::satisfies`].
+	///
+	/// The native architecture is the one of the cache, see
+	/// [`Cache::native_arch`].
+	pub fn satisfies_arch(&self, qualifier: &ArchQualifier, from_arch: &str) -> bool {
+		let native = self.cache.native_arch();
+		self.multi_arch()
+			.satisfies(qualifier, self.arch(), from_arch, native)
+	}
+
 	/// Returns a reference to the Dependency Map owned by the Version
 	///
 	/// Dependencies are in a `Vec<Dependency>`
@@ -138,6 +269,54 @@ impl<'a> Version<'a> {
 	/// Get the translated short description
 	pub fn summary(&self) -> Option<String> { self.desc_lookup()?.short_desc() }
 
+	/// Returns an iterator of every available Description for the version.
+	///
+	/// There is one Description for each translation that was found
+	/// plus the untranslated one from the Packages file.
+	pub fn descriptions(&self) -> impl Iterator<Item = Description<'a>> {
+		unsafe { self.description_list() }
+			.raw_iter()
+			.map(|d| Description::new(d, self.cache))
+	}
+
+	/// Get the Description for the first language in the list that has one.
+	///
+	/// The languages are codes like in [`Description::language`], ex: `de` or
+	/// `pt_BR`. `en` will match the untranslated description, and if nothing
+	/// matches that is used as the fallback.
+	///
+	/// The special values of `Acquire::Languages` such as `environment` and
+	/// `none` are not expanded, so pass the codes themselves.
+	///
+	/// # Example:
+	/// ```
+	/// use oma_apt::new_cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// let cand = cache.get("apt").unwrap().candidate().unwrap();
+	///
+	/// // Ask for German first, and then English
+	/// let desc = cand.description_for(&["de", "en"]).unwrap();
+	/// println!("{}: {}", desc.language(), desc.summary().unwrap());
+	/// ```
+	pub fn description_for<T: AsRef<str>>(&self, languages: &[T]) -> Option<Description<'a>> {
+		for lang in languages.iter().map(|l| l.as_ref()) {
+			if let Some(desc) = self.descriptions().find(|d| d.language() == lang) {
+				return Some(desc);
+			}
+
+			if lang == "en" {
+				if let Some(desc) = self.descriptions().find(|d| d.language().is_empty()) {
+					return Some(desc);
+				}
+			}
+		}
+
+		self.descriptions()
+			.find(|d| d.language().is_empty())
+			.or_else(|| self.descriptions().next())
+	}
+
 	/// Get data from the specified record field
 	///
 	/// # Returns:
@@ -278,7 +457,7 @@ pub(crate) mod raw {
 		/// The uncompressed size of the .deb file.
 		pub fn installed_size(self: &VerIterator) -> u64;
 
-		// TODO: Possibly return an enum
+		/// The raw Multi-Arch flags. See [`crate::MultiArch`].
 		pub fn multi_arch(self: &VerIterator) -> u8;
 
 		/// String representing MultiArch flag
@@ -348,6 +527,18 @@ pub(crate) mod raw {
 		/// The returned UniquePtr cannot outlive the cache.
 		unsafe fn translated_desc(self: &VerIterator) -> UniquePtr<DescIterator>;
 
+		/// Return every description of the version, one per translation.
+		///
+		/// # Safety
+		///
+		/// If the inner pointer is null segfaults can occur.
+		///
+		/// Using [`crate::raw::IntoRawIter::make_safe`] to convert to an Option
+		/// is recommended.
+		///
+		/// The returned UniquePtr cannot outlive the cache.
+		unsafe fn description_list(self: &VerIterator) -> UniquePtr<DescIterator>;
+
 		#[cxx_name = "Index"]
 		pub fn index(self: &VerIterator) -> u64;
 		/// Clone the pointer.
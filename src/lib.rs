@@ -19,23 +19,34 @@
 mod macros;
 mod acquire;
 pub mod cache;
+pub mod check;
 pub mod config;
+#[cfg(feature = "deb")]
+pub mod deb;
 mod depcache;
+pub mod dpkg;
+pub mod edsp;
 pub mod error;
+pub mod graph;
 mod iterators;
 mod pkgmanager;
 pub mod progress;
 pub mod records;
+#[cfg(feature = "deb")]
+pub mod repo;
+pub mod search;
 pub mod tagfile;
 pub mod util;
+pub mod why;
 
 #[doc(inline)]
-pub use cache::{Cache, PackageSort};
+pub use cache::{Cache, CacheBuilder, PackageSort, ResolveResult};
+pub use depcache::{ActionGroupGuard, Savepoint};
 pub use iterators::dependency::{create_depends_map, BaseDep, DepFlags, DepType, Dependency};
-pub use iterators::files::{PackageFile, VersionFile};
+pub use iterators::files::{Description, PackageFile, VersionFile};
 pub use iterators::package::{Marked, Package, PkgCurrentState, PkgInstState, PkgSelectedState};
 pub use iterators::provider::Provider;
-pub use iterators::version::Version;
+pub use iterators::version::{ArchQualifier, MultiArch, Version};
 
 /// C++ bindings for libapt-pkg
 pub mod raw {
@@ -44,7 +55,9 @@ pub mod raw {
 		PkgAcquire,
 	};
 	pub use crate::cache::raw::{create_cache, PkgCacheFile};
-	pub use crate::depcache::raw::{ActionGroup, PkgDepCache};
+	pub use crate::depcache::raw::{ActionGroup, DepCacheSavepoint, PkgDepCache};
+	pub use crate::dpkg::raw::{create_state_changes, StateChanges};
+	pub use crate::edsp::raw::{read_request, read_response, write_request};
 	pub use crate::iterators::{
 		DepIterator, DescIterator, PkgFileIterator, PkgIterator, PrvIterator, VerFileIterator,
 		VerIterator,
@@ -103,6 +116,7 @@ impl_deref!(
 	Dependency<'a> -> Vec<BaseDep<'a>>,
 	BaseDep<'a> -> raw::DepIterator,
 	Provider<'a> -> raw::PrvIterator,
+	Description<'a> -> raw::DescIterator,
 	VersionFile<'a> -> raw::VerFileIterator,
 	PackageFile<'a> -> raw::PkgFileIterator,
 );
@@ -112,6 +126,7 @@ impl_partial_eq!(
 	Package<'a>,
 	BaseDep<'a>,
 	Provider<'a>,
+	Description<'a>,
 	VersionFile<'a>,
 	PackageFile<'a>,
 );
@@ -121,6 +136,7 @@ impl_hash_eq!(
 	Version<'a>,
 	BaseDep<'a>,
 	Provider<'a>,
+	Description<'a>,
 	VersionFile<'a>,
 	PackageFile<'a>,
 );
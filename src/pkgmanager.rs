@@ -55,10 +55,19 @@ pub(crate) mod raw {
 		pub fn clear(self: &ProblemResolver, pkg: &PkgIterator);
 		pub fn protect(self: &ProblemResolver, pkg: &PkgIterator);
 
+		/// Tell the resolver that the package is to be removed.
+		/// A protected package stays removed.
+		pub fn remove(self: &ProblemResolver, pkg: &PkgIterator);
+
 		fn resolve(
 			self: &ProblemResolver,
 			fix_broken: bool,
 			op_progress: Pin<&mut OperationProgress>,
 		) -> Result<()>;
+
+		fn resolve_by_keep(
+			self: &ProblemResolver,
+			op_progress: Pin<&mut OperationProgress>,
+		) -> Result<()>;
 	}
 }
@@ -1,10 +1,12 @@
 //! Contains Progress struct for updating the package list.
 use std::fmt::Write as _;
-use std::io::{stdout, Write};
+use std::io::{stdout, BufRead, Write};
 use std::os::fd::RawFd;
 use std::pin::Pin;
 
 use cxx::{ExternType, UniquePtr};
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
 use crate::config::Config;
 use crate::error::raw::pending_error;
@@ -56,6 +58,266 @@ pub trait DynInstallProgress {
 		action: String,
 	);
 	fn error(&mut self, pkgname: String, steps_done: u64, total_steps: u64, error: String);
+
+	/// Called with every status change, error and conffile prompt as a typed
+	/// event, after [`DynInstallProgress::status_changed`] or
+	/// [`DynInstallProgress::error`].
+	fn event(&mut self, _event: InstallEvent) {}
+}
+
+/// What dpkg is doing with a package.
+///
+/// This is read from the human readable action apt sends, which apt
+/// translates. A frontend that sets a locale with `setlocale` gets
+/// translated messages, and every one of them is [`InstallAction::Other`].
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum InstallAction {
+	/// apt started dpkg. This is sent for each time dpkg is run.
+	RunningDpkg,
+	/// The package is about to be installed.
+	Installing,
+	/// dpkg is preparing to unpack the package.
+	Preparing,
+	Unpacking,
+	PreparingConfigure,
+	Configuring,
+	Installed,
+	PreparingRemove,
+	Removing,
+	Removed,
+	PreparingPurge,
+	Purging,
+	Purged,
+	/// Triggers of the package are being run.
+	Triggers,
+	/// The package disappeared because all of its files were taken over.
+	Disappearing,
+	/// A message that isn't known.
+	Other,
+}
+
+/// The start of the messages apt sends for each action.
+///
+/// The longer messages come first where they start the same way.
+const INSTALL_ACTIONS: [(&str, InstallAction); 16] = [
+	("Running dpkg", InstallAction::RunningDpkg),
+	("Installing ", InstallAction::Installing),
+	("Preparing to configure ", InstallAction::PreparingConfigure),
+	("Preparing for removal of ", InstallAction::PreparingRemove),
+	(
+		"Preparing to completely remove ",
+		InstallAction::PreparingPurge,
+	),
+	("Preparing ", InstallAction::Preparing),
+	("Unpacking ", InstallAction::Unpacking),
+	("Configuring ", InstallAction::Configuring),
+	("Installed ", InstallAction::Installed),
+	("Removing ", InstallAction::Removing),
+	("Removed ", InstallAction::Removed),
+	("Completely removing ", InstallAction::Purging),
+	("Completely removed ", InstallAction::Purged),
+	(
+		"Running post-installation trigger ",
+		InstallAction::Triggers,
+	),
+	("Processing triggers for ", InstallAction::Triggers),
+	("Noting disappearance of ", InstallAction::Disappearing),
+];
+
+impl InstallAction {
+	/// Find the action from the message of apt. ex: `Unpacking foo (amd64)`
+	pub fn from_message(message: &str) -> InstallAction {
+		INSTALL_ACTIONS
+			.iter()
+			.find(|(start, _)| message.starts_with(start))
+			.map_or(InstallAction::Other, |(_, action)| *action)
+	}
+}
+
+/// dpkg asking what to do with a conffile that was changed on the system and
+/// in the new version of the package.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ConffilePrompt {
+	/// The conffile on the system. ex: `/etc/foo.conf`
+	pub current: String,
+	/// The conffile from the package. ex: `/etc/foo.conf.dpkg-new`
+	pub new: String,
+	/// True if the conffile on the system was edited.
+	pub user_edited: bool,
+	/// True if the conffile was changed in the package.
+	pub dist_edited: bool,
+}
+
+impl ConffilePrompt {
+	/// Parse the message of dpkg. ex: `'/etc/foo' '/etc/foo.dpkg-new' 1 1`
+	pub fn parse(message: &str) -> Option<ConffilePrompt> {
+		let rest = message.trim().strip_prefix('\'')?;
+		let (current, rest) = rest.split_once("' '")?;
+		let (new, rest) = rest.split_once('\'')?;
+
+		let mut flags = rest.split_whitespace();
+		Some(ConffilePrompt {
+			current: current.to_string(),
+			new: new.to_string(),
+			user_edited: flags.next().is_some_and(|flag| flag == "1"),
+			dist_edited: flags.next().is_some_and(|flag| flag == "1"),
+		})
+	}
+}
+
+/// An event from dpkg while packages are installed.
+///
+/// `percent` is how much of the whole transaction is done.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstallEvent {
+	/// The state of a package changed.
+	Status {
+		/// The name of the package, with the architecture if apt knows it.
+		/// ex: `apt:amd64`
+		package: String,
+		percent: f32,
+		action: InstallAction,
+		/// The message of apt. ex: `Unpacking apt (amd64)`
+		message: String,
+	},
+	/// dpkg failed to process a package.
+	Error {
+		/// The name of the package, or the path of the `.deb` if dpkg
+		/// failed while unpacking it.
+		package: String,
+		percent: f32,
+		message: String,
+	},
+	/// dpkg is asking what to do with a conffile, and is waiting for an
+	/// answer on the terminal.
+	ConffilePrompt {
+		/// What apt sends as the name of the package.
+		///
+		/// dpkg puts the conffile in this place of its status line,
+		/// so this is usually the same as [`ConffilePrompt::current`].
+		package: String,
+		percent: f32,
+		prompt: ConffilePrompt,
+	},
+}
+
+impl InstallEvent {
+	/// Parse a line written to [`InstallProgress::Fd`].
+	///
+	/// ex: `pmstatus:apt:amd64:50:Unpacking apt (amd64)`
+	///
+	/// Returns [`None`] for any other line, such as the download status.
+	pub fn parse_line(line: &str) -> Option<InstallEvent> {
+		let (kind, rest) = line.trim_end_matches(['\r', '\n']).split_once(':')?;
+		if !matches!(kind, "pmstatus" | "pmerror" | "pmconffile") {
+			return None;
+		}
+
+		// The package name can have an architecture after a ':',
+		// so it ends before the first field that is a number.
+		let mut fields = rest.splitn(3, ':');
+		let first = fields.next()?;
+		let second = fields.next()?;
+		let (package, percent, message) = match second.parse::<f32>() {
+			Ok(percent) => (first.to_string(), percent, fields.next()?.to_string()),
+			Err(_) => {
+				let (percent, message) = fields.next()?.split_once(':')?;
+				(
+					format!("{first}:{second}"),
+					percent.parse().ok()?,
+					message.to_string(),
+				)
+			},
+		};
+
+		Some(match kind {
+			"pmstatus" => InstallEvent::Status {
+				package,
+				percent,
+				action: InstallAction::from_message(&message),
+				message,
+			},
+			"pmerror" => InstallEvent::Error {
+				package,
+				percent,
+				message,
+			},
+			_ => InstallEvent::ConffilePrompt {
+				package,
+				percent,
+				prompt: ConffilePrompt::parse(&message)?,
+			},
+		})
+	}
+}
+
+/// The percent of the transaction that is done, as apt sends it.
+fn percent(steps_done: u64, total_steps: u64) -> f32 {
+	if total_steps == 0 {
+		return 0.0;
+	}
+	steps_done as f32 / total_steps as f32 * 100.0
+}
+
+/// Read the events from the other end of [`InstallProgress::Fd`].
+///
+/// Lines that aren't install events are skipped.
+/// The iterator ends when the writer is closed or reading fails.
+///
+/// # Example:
+/// ```no_run
+/// use std::fs::File;
+/// use std::io::BufReader;
+/// use std::os::fd::{FromRawFd, IntoRawFd};
+///
+/// use oma_apt::new_cache;
+/// use oma_apt::progress::{InstallEvents, InstallProgress};
+///
+/// let (reader, writer) = std::io::pipe().unwrap();
+/// let events = std::thread::spawn(move || {
+///     for event in InstallEvents::new(BufReader::new(reader)) {
+///         println!("{event:?}");
+///     }
+/// });
+///
+/// let cache = new_cache!().unwrap();
+/// let fd = writer.into_raw_fd();
+/// cache.do_install(&mut InstallProgress::fd(fd)).unwrap();
+///
+/// // Close the pipe so the events end.
+/// drop(unsafe { File::from_raw_fd(fd) });
+/// events.join().unwrap();
+/// ```
+pub struct InstallEvents<R> {
+	reader: R,
+}
+
+impl<R: BufRead> InstallEvents<R> {
+	pub fn new(reader: R) -> Self { Self { reader } }
+}
+
+impl<R: BufRead> Iterator for InstallEvents<R> {
+	type Item = InstallEvent;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let mut line = vec![];
+		loop {
+			line.clear();
+			// File names in the messages don't have to be UTF-8,
+			// so only the end of the stream or a failed read stops the events.
+			match self.reader.read_until(b'\n', &mut line) {
+				Ok(0) | Err(_) => return None,
+				Ok(_) => {
+					if let Some(event) = InstallEvent::parse_line(&String::from_utf8_lossy(&line)) {
+						return Some(event);
+					}
+				},
+			}
+		}
+	}
 }
 
 /// A struct aligning with `apt`'s AcquireStatus.
@@ -237,14 +499,44 @@ impl<'a> InstallProgressFancy<'a> {
 		total_steps: u64,
 		action: String,
 	) {
+		let event = InstallEvent::Status {
+			package: pkgname.clone(),
+			percent: percent(steps_done, total_steps),
+			action: InstallAction::from_message(&action),
+			message: action.clone(),
+		};
+
 		self.inner
-			.status_changed(pkgname, steps_done, total_steps, action)
+			.status_changed(pkgname, steps_done, total_steps, action);
+		self.inner.event(event);
 	}
 
 	fn error(&mut self, pkgname: String, steps_done: u64, total_steps: u64, error: String) {
-		self.inner.error(pkgname, steps_done, total_steps, error)
+		let event = InstallEvent::Error {
+			package: pkgname.clone(),
+			percent: percent(steps_done, total_steps),
+			message: error.clone(),
+		};
+
+		self.inner.error(pkgname, steps_done, total_steps, error);
+		self.inner.event(event);
 	}
 
+	fn conffile_prompt(
+		&mut self,
+		pkgname: String,
+		steps_done: u64,
+		total_steps: u64,
+		message: String,
+	) {
+		if let Some(prompt) = ConffilePrompt::parse(&message) {
+			self.inner.event(InstallEvent::ConffilePrompt {
+				package: pkgname,
+				percent: percent(steps_done, total_steps),
+				prompt,
+			});
+		}
+	}
 
 	pub fn pin(&mut self) -> Pin<&mut InstallProgressFancy<'a>> { Pin::new(self) }
 }
@@ -631,7 +923,7 @@ impl DynInstallProgress for AptInstallProgress {
 		std::io::stdout().flush().unwrap();
 	}
 
-	// TODO: Need to figure out when to use this.
+	// dpkg prints the error on the terminal itself.
 	fn error(&mut self, _pkgname: String, _steps_done: u64, _total_steps: u64, _error: String) {}
 }
 
@@ -658,9 +950,7 @@ pub(crate) mod raw {
 			action: String,
 		);
 
-		// TODO: What kind of errors can be returned here?
-		// Research and update higher level structs as well
-		// TODO: Create custom errors when we have better information
+		/// Called when dpkg fails to process a package.
 		fn error(
 			self: &mut InstallProgressFancy,
 			pkgname: String,
@@ -669,6 +959,15 @@ pub(crate) mod raw {
 			error: String,
 		);
 
+		/// Called when dpkg asks what to do with a modified conffile.
+		fn conffile_prompt(
+			self: &mut InstallProgressFancy,
+			pkgname: String,
+			steps_done: u64,
+			total_steps: u64,
+			message: String,
+		);
+
 		/// Called on c++ to set the pulse interval.
 		fn pulse_interval(self: &mut AcquireProgress) -> usize;
 
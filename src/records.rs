@@ -15,7 +15,7 @@ use cxx::UniquePtr;
 ///
 /// Other Known Record Keys:
 ///
-/// `Conffiles` `Status` `Python-Version` `Auto-Built-Package`
+/// `Status` `Python-Version` `Auto-Built-Package`
 /// `Enhances` `Cnf-Extra-Commands` `Gstreamer-Elements`
 /// `Gstreamer-Encoders` `Lua-Versions` `Original-Maintainer` `Protected`
 /// `Gstreamer-Uri-Sources` `Vendor` `Build-Ids` `Efi-Vendor` `SHA512`
@@ -108,6 +108,10 @@ pub mod RecordField {
 	/// Either `allowed`, `foreign`, or `same`
 	pub const MultiArch: &str = "Multi-Arch";
 
+	/// The configuration files of an installed package, one per line.
+	/// `/etc/apt/apt.conf.d/01autoremove 2b9ee3c0b8f7dd8fd4c1b6d5a7f8f6b3`
+	pub const Conffiles: &str = "Conffiles";
+
 	/// The section of the package `admin`
 	pub const Section: &str = "Section";
 
@@ -129,10 +133,20 @@ pub mod RecordField {
 	pub const SHA256: &str = "SHA256";
 }
 
+/// Which kind of file the Parser was last moved to.
+///
+/// Version files and Description files are indexed separately,
+/// so the index alone is not enough to know if we can skip the lookup.
+#[derive(PartialEq, Eq)]
+enum Lookup {
+	Ver(u64),
+	Desc(u64),
+}
+
 pub struct PackageRecords {
 	pub(crate) ptr: UniquePtr<raw::PkgRecords>,
 	parser: RefCell<UniquePtr<raw::Parser>>,
-	index: RefCell<u64>,
+	index: RefCell<Option<Lookup>>,
 }
 
 impl PackageRecords {
@@ -140,15 +154,15 @@ impl PackageRecords {
 		PackageRecords {
 			ptr,
 			parser: RefCell::new(UniquePtr::null()),
-			index: RefCell::new(0),
+			index: RefCell::new(None),
 		}
 	}
 
-	fn replace_index(&self, index: u64) -> bool {
-		if self.index.borrow().eq(&index) {
+	fn replace_index(&self, index: Lookup) -> bool {
+		if self.index.borrow().as_ref() == Some(&index) {
 			return false;
 		}
-		self.index.replace(index);
+		self.index.replace(Some(index));
 		true
 	}
 
@@ -160,14 +174,14 @@ impl PackageRecords {
 	}
 
 	pub fn ver_lookup(&self, file: &raw::VerFileIterator) -> &PackageRecords {
-		if self.replace_index(file.index()) {
+		if self.replace_index(Lookup::Ver(file.index())) {
 			unsafe { self.parser.replace(self.ptr.ver_lookup(file)) };
 		}
 		self
 	}
 
 	pub fn desc_lookup(&self, file: &raw::DescIterator) -> &PackageRecords {
-		if self.replace_index(file.index()) {
+		if self.replace_index(Lookup::Desc(file.index())) {
 			unsafe { self.parser.replace(self.ptr.desc_lookup(file)) };
 		}
 		self
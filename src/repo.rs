@@ -0,0 +1,152 @@
+//! Generate a local repository from `.deb` files.
+//!
+//! This does what `apt-ftparchive packages` and `apt-ftparchive release` do
+//! for a flat repository. Every `.deb` under the directory is indexed into
+//! `Packages` and `Packages.xz`, and a `Release` with the hashes of both is
+//! written next to them.
+//!
+//! The Release isn't signed, so the source needs `trusted=yes`:
+//!
+//! `deb [trusted=yes] file:/path/to/repo ./`
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::deb::{walk_dir, Compression, DebFile};
+use crate::error::AptErrors;
+use crate::tagfile::TagSection;
+use crate::util::hash_file;
+
+/// The hashes written to the index files.
+///
+/// These are the names used in Release files,
+/// and the names used in Packages files.
+const HASHES: [(&str, &str); 4] = [
+	("MD5Sum", "MD5sum"),
+	("SHA1", "SHA1"),
+	("SHA256", "SHA256"),
+	("SHA512", "SHA512"),
+];
+
+/// Build a flat repository in a directory of `.deb` files.
+///
+/// # Example:
+/// ```no_run
+/// use oma_apt::repo::RepoBuilder;
+///
+/// RepoBuilder::new("/srv/repo")
+///     .origin("My Company")
+///     .suite("stable")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RepoBuilder {
+	dir: PathBuf,
+	release: TagSection,
+}
+
+impl RepoBuilder {
+	pub fn new<P: AsRef<Path>>(dir: P) -> RepoBuilder {
+		RepoBuilder {
+			dir: dir.as_ref().to_path_buf(),
+			release: TagSection::default(),
+		}
+	}
+
+	/// The Origin field of the Release, used for pinning.
+	pub fn origin(self, origin: &str) -> RepoBuilder { self.field("Origin", origin) }
+
+	/// The Label field of the Release.
+	pub fn label(self, label: &str) -> RepoBuilder { self.field("Label", label) }
+
+	/// The Suite field of the Release. ex: `stable`
+	pub fn suite(self, suite: &str) -> RepoBuilder { self.field("Suite", suite) }
+
+	/// The Codename field of the Release. ex: `bookworm`
+	pub fn codename(self, codename: &str) -> RepoBuilder { self.field("Codename", codename) }
+
+	/// The architectures the repository has packages for.
+	///
+	/// apt ignores a repository that doesn't list the native architecture,
+	/// so this is left out by default.
+	pub fn architectures<T: AsRef<str>>(self, archs: &[T]) -> RepoBuilder {
+		let archs: Vec<&str> = archs.iter().map(|arch| arch.as_ref()).collect();
+		self.field("Architectures", &archs.join(" "))
+	}
+
+	/// Any other field of the Release. ex: `Description`
+	pub fn field(mut self, key: &str, value: &str) -> RepoBuilder {
+		self.release.set(key, value);
+		self
+	}
+
+	/// Index the `.deb` files and write `Packages`, `Packages.xz` and
+	/// `Release`.
+	///
+	/// An old `InRelease` or `Release.gpg` is removed,
+	/// apt would reject the new files because of them.
+	pub fn build(&self) -> Result<(), AptErrors> {
+		let packages = self.packages()?;
+
+		let xz = self.dir.join("Packages.xz");
+		fs::write(self.dir.join("Packages"), &packages)?;
+		fs::write(&xz, Compression::Xz.compress(packages.as_bytes())?)?;
+
+		for signed in ["InRelease", "Release.gpg"] {
+			let path = self.dir.join(signed);
+			if path.exists() {
+				fs::remove_file(path)?;
+			}
+		}
+
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map_or(0, |time| time.as_secs());
+
+		let mut release = self.release.clone();
+		release.set("Date", &crate::util::raw::time_rfc1123(now as i64));
+
+		for (name, _) in HASHES {
+			let mut value = String::new();
+			for index in ["Packages", "Packages.xz"] {
+				let path = self.dir.join(index);
+				let size = fs::metadata(&path)?.len();
+				value += &format!("\n {} {size:>16} {index}", hash_file(&path, name)?);
+			}
+			release.set(name, &value);
+		}
+
+		fs::write(self.dir.join("Release"), release.to_string())?;
+		Ok(())
+	}
+
+	/// The stanzas of every `.deb` under the directory.
+	fn packages(&self) -> Result<String, AptErrors> {
+		let mut paths = vec![];
+		walk_dir(&self.dir, Path::new(""), &mut paths)?;
+
+		let mut stanzas = vec![];
+		for rel in paths {
+			if rel.extension().and_then(|ext| ext.to_str()) != Some("deb") {
+				continue;
+			}
+
+			let path = self.dir.join(&rel);
+			if !path.is_file() {
+				continue;
+			}
+
+			let deb = DebFile::open(&path)?;
+			let mut stanza = deb.control().clone();
+			stanza.set("Filename", &format!("./{}", rel.display()));
+			stanza.set("Size", &fs::metadata(&path)?.len().to_string());
+			for (name, field) in HASHES {
+				stanza.set(field, &hash_file(&path, name)?);
+			}
+			stanzas.push(stanza.to_string());
+		}
+
+		Ok(stanzas.join("\n"))
+	}
+}
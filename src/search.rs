@@ -0,0 +1,243 @@
+//! Full text search over package names, provides and descriptions.
+//!
+//! This works like `apt-cache search`. A query is split on whitespace and
+//! every term is treated as a case insensitive regular expression. A package
+//! only matches if all of the terms match it somewhere.
+//!
+//! For repeated searches build a [`SearchIndex`] once and query it as many
+//! times as you like. The index holds all of the text that is searched so no
+//! further record lookups are required.
+
+use regex::{Regex, RegexBuilder};
+
+use crate::cache::PackageSort;
+use crate::error::AptErrors;
+use crate::{Cache, Package};
+
+/// Score given when a term matches the whole package name.
+const EXACT_NAME: u32 = 100;
+/// Score given when a term matches at the start of the package name.
+const NAME_PREFIX: u32 = 60;
+/// Score given when a term matches anywhere in the package name.
+const NAME: u32 = 40;
+/// Score given when a term matches a name that the package provides.
+const PROVIDES: u32 = 30;
+/// Score given when a term matches the short description.
+const SUMMARY: u32 = 20;
+/// Score given when a term only matches the long description.
+const DESCRIPTION: u32 = 10;
+
+/// Determines what and how to search.
+///
+/// # Example:
+/// ```
+/// use oma_apt::new_cache;
+/// use oma_apt::search::SearchQuery;
+///
+/// let cache = new_cache!().unwrap();
+/// let query = SearchQuery::new("package manager");
+///
+/// for result in cache.search(&query).unwrap() {
+///     println!("{} ({})", result.package.name(), result.score);
+/// }
+/// ```
+pub struct SearchQuery {
+	terms: Vec<String>,
+	names_only: bool,
+}
+
+impl SearchQuery {
+	/// Create a new query. Each whitespace separated word is a term.
+	pub fn new(query: &str) -> SearchQuery {
+		SearchQuery {
+			terms: query.split_whitespace().map(|t| t.to_string()).collect(),
+			names_only: false,
+		}
+	}
+
+	/// Only match against package names and the names they provide.
+	///
+	/// Equivalent to `apt-cache search --names-only`.
+	pub fn names_only(mut self) -> Self {
+		self.names_only = true;
+		self
+	}
+
+	/// Compile the terms into case insensitive regular expressions.
+	fn compile(&self) -> Result<Vec<Regex>, AptErrors> {
+		self.terms
+			.iter()
+			.map(|term| {
+				RegexBuilder::new(term)
+					.case_insensitive(true)
+					.build()
+					.map_err(|e| AptErrors::from(e.to_string()))
+			})
+			.collect()
+	}
+}
+
+/// A single package that matched a [`SearchQuery`].
+#[derive(Debug)]
+pub struct SearchResult<'a> {
+	/// The package that matched.
+	pub package: Package<'a>,
+	/// How well the package matched. Higher is better.
+	pub score: u32,
+}
+
+/// The searchable text of a single package.
+struct IndexEntry<'a> {
+	pkg: Package<'a>,
+	provides: Vec<String>,
+	summary: String,
+	description: String,
+}
+
+impl<'a> IndexEntry<'a> {
+	/// Gather the text for a package from its candidate,
+	/// or the newest version if there is no candidate.
+	///
+	/// Descriptions require a records lookup, so they are skipped
+	/// unless `descriptions` is [`true`].
+	fn new(pkg: Package<'a>, descriptions: bool) -> Option<IndexEntry<'a>> {
+		let ver = pkg.candidate().or_else(|| pkg.versions().next())?;
+
+		let (summary, description) = if descriptions {
+			(
+				ver.summary().unwrap_or_default(),
+				ver.description().unwrap_or_default(),
+			)
+		} else {
+			Default::default()
+		};
+
+		Some(IndexEntry {
+			provides: ver.provides().map(|p| p.name().to_string()).collect(),
+			summary,
+			description,
+			pkg,
+		})
+	}
+
+	/// Score this entry against every term.
+	///
+	/// Returns [`None`] if any of the terms did not match.
+	fn score(&self, terms: &[Regex], names_only: bool) -> Option<u32> {
+		let name = self.pkg.name();
+		let mut score = 0;
+
+		for term in terms {
+			score += if let Some(found) = term.find(name) {
+				match (found.start(), found.end()) {
+					(0, end) if end == name.len() => EXACT_NAME,
+					(0, _) => NAME_PREFIX,
+					_ => NAME,
+				}
+			} else if self.provides.iter().any(|p| term.is_match(p)) {
+				PROVIDES
+			} else if names_only {
+				return None;
+			} else if term.is_match(&self.summary) {
+				SUMMARY
+			} else if term.is_match(&self.description) {
+				DESCRIPTION
+			} else {
+				return None;
+			};
+		}
+		Some(score)
+	}
+}
+
+/// Sort results by score, best first, and then by name.
+fn rank(results: &mut [SearchResult]) {
+	results.sort_by(|a, b| {
+		b.score
+			.cmp(&a.score)
+			.then_with(|| a.package.name().cmp(b.package.name()))
+	});
+}
+
+/// An in memory index of all of the text that can be searched.
+///
+/// Building the index requires a records lookup for every package,
+/// searching it afterwards does not.
+///
+/// # Example:
+/// ```
+/// use oma_apt::new_cache;
+/// use oma_apt::search::{SearchIndex, SearchQuery};
+///
+/// let cache = new_cache!().unwrap();
+/// let index = SearchIndex::new(&cache);
+///
+/// for query in ["rust", "python3 http"] {
+///     let results = index.search(&SearchQuery::new(query)).unwrap();
+///     println!("{query}: {} results", results.len());
+/// }
+/// ```
+pub struct SearchIndex<'a> {
+	entries: Vec<IndexEntry<'a>>,
+}
+
+impl<'a> SearchIndex<'a> {
+	/// Build the index from every package in the cache that has versions.
+	pub fn new(cache: &'a Cache) -> SearchIndex<'a> {
+		SearchIndex {
+			entries: cache
+				.packages(&PackageSort::default())
+				.filter_map(|pkg| IndexEntry::new(pkg, true))
+				.collect(),
+		}
+	}
+
+	/// The amount of packages in the index.
+	pub fn len(&self) -> usize { self.entries.len() }
+
+	/// True if there are no packages in the index.
+	pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+	/// Search the index. Results are ranked with the best match first.
+	///
+	/// Returns [`Err`] if one of the terms is not a valid regular expression.
+	pub fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult<'a>>, AptErrors> {
+		let terms = query.compile()?;
+
+		let mut results: Vec<_> = self
+			.entries
+			.iter()
+			.filter_map(|entry| {
+				Some(SearchResult {
+					score: entry.score(&terms, query.names_only)?,
+					package: entry.pkg.clone(),
+				})
+			})
+			.collect();
+
+		rank(&mut results);
+		Ok(results)
+	}
+}
+
+/// Search every package in the cache without building an index.
+pub(crate) fn search_cache<'a>(
+	cache: &'a Cache,
+	query: &SearchQuery,
+) -> Result<Vec<SearchResult<'a>>, AptErrors> {
+	let terms = query.compile()?;
+
+	let mut results: Vec<_> = cache
+		.packages(&PackageSort::default())
+		.filter_map(|pkg| IndexEntry::new(pkg, !query.names_only))
+		.filter_map(|entry| {
+			Some(SearchResult {
+				score: entry.score(&terms, query.names_only)?,
+				package: entry.pkg,
+			})
+		})
+		.collect();
+
+	rank(&mut results);
+	Ok(results)
+}
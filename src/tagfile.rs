@@ -25,9 +25,13 @@ impl std::error::Error for ParserError {}
 
 /// A section in a TagFile. A TagFile is made up of double-newline (`\n\n`)
 /// separated paragraphs, each of which make up one of these sections.
-#[derive(Debug)]
+///
+/// The order of the keys is kept, so a section is written back the same way
+/// it was parsed.
+#[derive(Debug, Default, Clone)]
 pub struct TagSection {
 	data: HashMap<String, String>,
+	keys: Vec<String>,
 }
 
 impl From<TagSection> for HashMap<String, String> {
@@ -68,7 +72,7 @@ impl TagSection {
 		}
 
 		// Start building up the HashMap.
-		let mut data = HashMap::new();
+		let mut tags = Self::default();
 		let lines = section.lines().collect::<Vec<&str>>();
 
 		// Variables used while parsing.
@@ -147,13 +151,13 @@ impl TagSection {
 				}
 
 				// Add the key and reset the `current_key` and `current_value` counters.
-				data.insert(current_key.unwrap(), current_value);
+				tags.set(&current_key.unwrap(), &current_value);
 				current_key = None;
 				current_value = String::new();
 			}
 		}
 
-		Ok(Self { data })
+		Ok(tags)
 	}
 
 	/// Get the underlying [`HashMap`] used in the generated [`TagSection`].
@@ -171,6 +175,46 @@ impl TagSection {
 		}
 		default
 	}
+
+	/// The keys in the order they were parsed or set.
+	pub fn keys(&self) -> impl Iterator<Item = &String> { self.keys.iter() }
+
+	/// Set the value of a key.
+	///
+	/// A new key is added at the end, an existing key keeps its place.
+	/// The value is stored like a parsed one, so lines after the first
+	/// need to be indented.
+	pub fn set(&mut self, key: &str, value: &str) {
+		if self
+			.data
+			.insert(key.to_string(), value.to_string())
+			.is_none()
+		{
+			self.keys.push(key.to_string());
+		}
+	}
+
+	/// Remove a key, returning its value.
+	pub fn remove(&mut self, key: &str) -> Option<String> {
+		self.keys.retain(|k| k != key);
+		self.data.remove(key)
+	}
+}
+
+impl fmt::Display for TagSection {
+	/// Write the section as it would be in a file, with a trailing newline.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for key in &self.keys {
+			let value = &self.data[key];
+			// A value that starts on the next line has no space after the colon.
+			if value.starts_with('\n') {
+				writeln!(f, "{key}:{value}")?;
+			} else {
+				writeln!(f, "{key}: {value}")?;
+			}
+		}
+		Ok(())
+	}
 }
 
 /// Parses a TagFile: these are files such as Debian `control` and `Packages`
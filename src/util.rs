@@ -1,10 +1,19 @@
 //! Contains miscellaneous helper utilities.
 use std::cmp::Ordering;
-
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io::ErrorKind;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::{fmt, process};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
 use terminal_size::{terminal_size, Height, Width};
 
 use crate::error::AptErrors;
-use crate::{config, Cache, DepFlags, Package};
+use crate::{config, Cache, DepFlags, DepType, Package};
 
 /// Get the terminal's height, i.e. the number of rows it has.
 ///
@@ -54,6 +63,7 @@ pub fn cmp_versions(ver1: &str, ver2: &str) -> Ordering {
 }
 
 /// Disk Space that `apt` will use for a transaction.
+#[derive(Debug)]
 pub enum DiskSpace {
 	/// Additional Disk Space required.
 	Require(u64),
@@ -194,87 +204,309 @@ pub fn apt_is_locked() -> bool {
 	raw::apt_is_locked()
 }
 
-/// Reference implementation to print broken packages just like apt does.
+/// Hash a file the same way apt checks downloads.
 ///
-/// ## Returns [`None`] if the package is not considered broken
+/// `hash_type` is the name used in Release files.
+/// `MD5Sum`, `SHA1`, `SHA256` or `SHA512`
+pub fn hash_file<P: AsRef<Path>>(path: P, hash_type: &str) -> Result<String, AptErrors> {
+	Ok(raw::hash_file(&path.as_ref().to_string_lossy(), hash_type)?)
+}
+
+/// Decompress a file with any of the compressors apt knows.
 ///
-/// ## now:
-///   * [true] = When checking broken packages before modifying the cache.
-///   * [false] = When checking broken packages after modifying the cache.
-pub fn show_broken_pkg(cache: &Cache, pkg: &Package, now: bool) -> Option<String> {
-	// If the package isn't broken for the state Return None
-	if (now && !pkg.is_now_broken()) || (!now && !pkg.is_inst_broken()) {
-		return None;
-	};
+/// The compression is picked from the extension of `from`, like `.gz`,
+/// `.xz` or `.zst`.
+pub(crate) fn decompress_file(from: &Path, to: &Path) -> Result<(), AptErrors> {
+	Ok(raw::decompress_file(
+		&from.to_string_lossy(),
+		&to.to_string_lossy(),
+	)?)
+}
 
-	let mut broken_string = String::new();
+/// A new directory in the temp dir that is removed on drop.
+///
+/// Like `mkdtemp`, the name is random and the directory must not exist yet,
+/// so a symlink planted by another user is never followed. Only the owner can
+/// use it.
+#[derive(Debug)]
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+	pub(crate) fn new(prefix: &str) -> Result<TempDir, AptErrors> {
+		for _ in 0..100 {
+			let random = RandomState::new().build_hasher().finish();
+			let path =
+				std::env::temp_dir().join(format!("{prefix}-{}-{random:016x}", process::id()));
+
+			match fs::DirBuilder::new().mode(0o700).create(&path) {
+				Ok(()) => return Ok(TempDir(path)),
+				Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+				Err(err) => return Err(err.into()),
+			}
+		}
+		Err(AptErrors::from(format!(
+			"Unable to create a temporary directory for {prefix}"
+		)))
+	}
 
-	broken_string += &format!(" {pkg} :");
+	pub(crate) fn path(&self) -> &Path { &self.0 }
+}
 
-	// Pick the proper version based on now status.
-	// else Return with just the package name like Apt does.
-	let Some(ver) = (match now {
-		true => pkg.installed(),
-		false => pkg.install_version(),
-	}) else {
-		broken_string += "\n";
-		return Some(broken_string);
-	};
+impl Drop for TempDir {
+	fn drop(&mut self) { let _ = fs::remove_dir_all(&self.0); }
+}
 
-	let indent = pkg.name().len() + 3;
-	let mut first = true;
+/// Why a single alternative of a broken dependency can not be satisfied.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum BrokenReason {
+	/// A different version of the target is going to be installed.
+	///
+	/// Contains the version that is going to be installed.
+	VersionMismatch(String),
+	/// The target has a candidate, but it is not going to be installed.
+	NotGoingToBeInstalled,
+	/// The target is provided by other packages.
+	///
+	/// apt does not give a reason for these.
+	Virtual,
+	/// The target is a virtual package and nothing provides it.
+	VirtualNoProvider,
+	/// The target has versions, but none of them can be installed.
+	NotInstallable,
+}
 
-	// ShowBrokenDeps
-	for dep in ver.depends_map().values().flatten() {
-		for (i, base_dep) in dep.iter().enumerate() {
-			if !cache.depcache().is_important_dep(base_dep) {
-				continue;
-			}
+impl fmt::Display for BrokenReason {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			BrokenReason::VersionMismatch(ver) => write!(f, "but {ver} is to be installed"),
+			BrokenReason::NotGoingToBeInstalled => write!(f, "but it is not going to be installed"),
+			BrokenReason::Virtual => Ok(()),
+			BrokenReason::VirtualNoProvider | BrokenReason::NotInstallable => {
+				write!(f, "but it is not installable")
+			},
+		}
+	}
+}
+
+/// A single alternative of a broken dependency.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BrokenDep {
+	/// The name of the target package.
+	pub name: String,
+	/// The comparison of the dependency, if there is one. ex: `>=`
+	pub comp_type: Option<String>,
+	/// The version the dependency asks for, if there is one.
+	pub version: Option<String>,
+	/// The version of the target that is going to be installed.
+	///
+	/// Falls back to the installed version and then the candidate, so
+	/// [`None`] means the target has no version at all.
+	pub target_version: Option<String>,
+	/// Why this alternative failed, [`None`] if it is satisfied.
+	pub reason: Option<BrokenReason>,
+}
+
+/// A dependency, including all of its alternatives, that is not satisfied.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BrokenGroup {
+	/// The type of the dependency.
+	pub dep_type: DepType,
+	/// Every alternative in the order they were tried.
+	pub alternatives: Vec<BrokenDep>,
+}
 
-			let dep_flag = if now { DepFlags::DepGNow } else { DepFlags::DepInstall };
+/// Everything that is broken about a single package.
+///
+/// The [`fmt::Display`] implementation renders this just like apt does.
+///
+/// # Example:
+/// ```
+/// use oma_apt::new_cache;
+/// use oma_apt::util::BrokenReport;
+///
+/// let cache = new_cache!().unwrap();
+/// let pkg = cache.get("apt").unwrap();
+///
+/// if let Some(report) = BrokenReport::new(&cache, &pkg, true) {
+///     for group in &report.groups {
+///         for alt in &group.alternatives {
+///             println!("{} {}: {:?}", group.dep_type, alt.name, alt.reason);
+///         }
+///     }
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BrokenReport {
+	/// The name of the broken package.
+	pub package: String,
+	/// The version that was checked.
+	///
+	/// [`None`] if there is no version to check, in which case apt only shows
+	/// the package name.
+	pub version: Option<String>,
+	/// Every dependency that is not satisfied.
+	pub groups: Vec<BrokenGroup>,
+}
 
-			if cache.depcache().dep_state(base_dep) & dep_flag == dep_flag {
+impl BrokenReport {
+	/// Find out what is broken about a package.
+	///
+	/// ## Returns [`None`] if the package is not considered broken
+	///
+	/// ## now:
+	///   * [true] = When checking broken packages before modifying the cache.
+	///   * [false] = When checking broken packages after modifying the cache.
+	pub fn new(cache: &Cache, pkg: &Package, now: bool) -> Option<BrokenReport> {
+		// If the package isn't broken for the state Return None
+		if (now && !pkg.is_now_broken()) || (!now && !pkg.is_inst_broken()) {
+			return None;
+		};
+
+		let mut report = BrokenReport {
+			package: pkg.name().to_string(),
+			version: None,
+			groups: vec![],
+		};
+
+		// Pick the proper version based on now status.
+		// else Return with just the package name like Apt does.
+		let Some(ver) = (match now {
+			true => pkg.installed(),
+			false => pkg.install_version(),
+		}) else {
+			return Some(report);
+		};
+		report.version = Some(ver.version().to_string());
+
+		let dep_flag = if now { DepFlags::DepGNow } else { DepFlags::DepInstall };
+
+		// ShowBrokenDeps
+		for dep in ver.depends_map().values().flatten() {
+			if !cache.depcache().is_important_dep(dep.first()) {
 				continue;
 			}
 
-			if !first {
-				broken_string += &" ".repeat(indent);
+			let alternatives: Vec<BrokenDep> = dep
+				.iter()
+				.map(|base_dep| {
+					let reason = if cache.depcache().dep_state(base_dep) & dep_flag == dep_flag {
+						None
+					} else {
+						Some(broken_reason(base_dep.target_package()))
+					};
+
+					let target = base_dep.target_package();
+					let target_version = target
+						.install_version()
+						.or_else(|| target.installed())
+						.or_else(|| target.candidate())
+						.map(|v| v.version().to_string());
+
+					BrokenDep {
+						name: target.name().to_string(),
+						comp_type: base_dep.comp_type().map(|c| c.to_string()),
+						version: base_dep.target_ver().ok().map(|v| v.to_string()),
+						target_version,
+						reason,
+					}
+				})
+				.collect();
+
+			if alternatives.iter().all(|alt| alt.reason.is_none()) {
+				continue;
 			}
-			first = false;
 
-			// If it's the first or Dep
-			if i > 0 {
-				broken_string += &" ".repeat(base_dep.dep_type().as_ref().len() + 3);
-			} else {
-				broken_string += &format!(" {}: ", base_dep.dep_type())
-			}
+			report.groups.push(BrokenGroup {
+				dep_type: dep.dep_type(),
+				alternatives,
+			});
+		}
+		Some(report)
+	}
+}
 
-			broken_string += base_dep.target_package().name();
+/// Figure out why the target of a dependency can not satisfy it.
+fn broken_reason(target: &Package) -> BrokenReason {
+	if target.has_provides() {
+		return BrokenReason::Virtual;
+	}
 
-			if let (Ok(ver_str), Some(comp)) = (base_dep.target_ver(), base_dep.comp_type()) {
-				broken_string += &format!(" ({comp} {ver_str})");
-			}
+	if let Some(target_ver) = target.install_version() {
+		BrokenReason::VersionMismatch(target_ver.version().to_string())
+	} else if target.candidate().is_some() {
+		BrokenReason::NotGoingToBeInstalled
+	} else if !target.has_versions() {
+		BrokenReason::VirtualNoProvider
+	} else {
+		BrokenReason::NotInstallable
+	}
+}
 
-			let target = base_dep.target_package();
-			if !target.has_provides() {
-				if let Some(target_ver) = target.install_version() {
-					broken_string += &format!(" but {target_ver} is to be installed")
-				} else if target.candidate().is_some() {
-					broken_string += " but it is not going to be installed";
-				} else if target.has_provides() {
-					broken_string += " but it is a virtual package";
+impl fmt::Display for BrokenReport {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, " {} :", self.package)?;
+
+		if self.version.is_none() {
+			return writeln!(f);
+		}
+
+		let indent = self.package.len() + 3;
+		let mut first = true;
+
+		for group in &self.groups {
+			for (i, alt) in group.alternatives.iter().enumerate() {
+				let Some(reason) = &alt.reason else {
+					continue;
+				};
+
+				if !first {
+					write!(f, "{}", " ".repeat(indent))?;
+				}
+				first = false;
+
+				// If it's the first or Dep
+				if i > 0 {
+					write!(f, "{}", " ".repeat(group.dep_type.as_ref().len() + 3))?;
 				} else {
-					broken_string += " but it is not installable";
+					write!(f, " {}: ", group.dep_type)?;
 				}
-			}
 
-			if i + 1 != dep.len() {
-				broken_string += " or"
+				write!(f, "{}", alt.name)?;
+
+				if let (Some(comp), Some(ver)) = (&alt.comp_type, &alt.version) {
+					write!(f, " ({comp} {ver})")?;
+				}
+
+				if *reason != BrokenReason::Virtual {
+					write!(f, " {reason}")?;
+				}
+
+				if i + 1 != group.alternatives.len() {
+					write!(f, " or")?;
+				}
+				writeln!(f)?;
 			}
-			broken_string += "\n";
 		}
+		Ok(())
 	}
-	Some(broken_string)
+}
+
+/// Reference implementation to print broken packages just like apt does.
+///
+/// This renders a [`BrokenReport`].
+/// Use that directly if you need to show the information yourself.
+///
+/// ## Returns [`None`] if the package is not considered broken
+///
+/// ## now:
+///   * [true] = When checking broken packages before modifying the cache.
+///   * [false] = When checking broken packages after modifying the cache.
+pub fn show_broken_pkg(cache: &Cache, pkg: &Package, now: bool) -> Option<String> {
+	Some(BrokenReport::new(cache, pkg, now)?.to_string())
 }
 
 #[cxx::bridge]
@@ -311,5 +543,15 @@ pub(crate) mod raw {
 
 		/// Check if the lockfile is locked.
 		pub fn apt_is_locked() -> bool;
+
+		/// Hash a file with libapt.
+		pub fn hash_file(path: &str, hash_type: &str) -> Result<String>;
+
+		/// Decompress a file, picking the compression from its extension.
+		pub fn decompress_file(from: &str, to: &str) -> Result<()>;
+
+		/// Format a unix time for the Date field of a Release file.
+		/// ex: `Thu, 01 Jan 1970 00:00:00 GMT`
+		pub fn time_rfc1123(time: i64) -> String;
 	}
 }
@@ -0,0 +1,257 @@
+//! Explain why a package is installed, or why it cannot be.
+//!
+//! This works like `aptitude why` and `aptitude why-not`.
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{BaseDep, DepType, Package, Version};
+
+/// How strongly a chain holds a package on the system.
+///
+/// Stronger chains sort first.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Strength {
+	/// Every link is a Depends or PreDepends.
+	Depends,
+	/// At least one link is a Recommends.
+	Recommends,
+}
+
+/// A single step in a [`WhyChain`].
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WhyLink {
+	/// The installed package that declares the dependency.
+	pub package: String,
+	/// The installed version of the package.
+	pub version: String,
+	/// The type of the dependency.
+	pub dep_type: DepType,
+	/// The relationship as it is written. ex: `libc6 (>= 2.34)`
+	pub relation: String,
+}
+
+/// A chain of dependencies from a manually installed package
+/// down to the package being explained.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WhyChain {
+	/// The first link is from a manually installed package.
+	pub links: Vec<WhyLink>,
+	/// The weakest dependency type in the chain.
+	pub strength: Strength,
+}
+
+impl WhyChain {
+	fn new(links: Vec<WhyLink>) -> WhyChain {
+		let strength = if links.iter().any(|l| l.dep_type == DepType::Recommends) {
+			Strength::Recommends
+		} else {
+			Strength::Depends
+		};
+		WhyChain { links, strength }
+	}
+
+	/// The manually installed package at the start of the chain.
+	pub fn root(&self) -> &str { &self.links[0].package }
+}
+
+impl fmt::Display for WhyChain {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for link in &self.links {
+			writeln!(f, "{} {} {}", link.package, link.dep_type, link.relation)?;
+		}
+		Ok(())
+	}
+}
+
+/// An installed package that prevents another from being installed.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WhyNot {
+	/// The installed package that is in the way.
+	pub package: String,
+	/// The installed version of the package.
+	pub version: String,
+	/// Either Conflicts or Breaks.
+	pub dep_type: DepType,
+	/// The relationship as it is written. ex: `apt (<< 2.0)`
+	pub relation: String,
+	/// The package that declares the relationship.
+	///
+	/// This is either the package being explained or [`WhyNot::package`].
+	pub declared_by: String,
+}
+
+impl fmt::Display for WhyNot {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"{} {} {}",
+			self.declared_by, self.dep_type, self.relation
+		)
+	}
+}
+
+/// Format a dependency the way it is written in the control file.
+fn relation(dep: &BaseDep) -> String {
+	let target = unsafe { dep.target_pkg() }.fullname(true);
+	match (dep.comp_type(), dep.target_ver().ok()) {
+		(Some(comp), Some(ver)) => format!("{target} ({comp} {ver})"),
+		_ => target,
+	}
+}
+
+/// Packages the reverse dependencies should be read from.
+///
+/// This is the package itself plus any virtual packages `ver` provides.
+fn rdepends_targets<'a>(pkg: &Package<'a>, ver: Option<Version<'a>>) -> Vec<Package<'a>> {
+	let mut targets = vec![pkg.clone()];
+	if let Some(ver) = ver {
+		for provider in ver.provides() {
+			if let Some(virt) = pkg.cache.get(provider.name()) {
+				targets.push(virt);
+			}
+		}
+	}
+	targets
+}
+
+/// Walk installed reverse dependencies breadth first
+/// until manually installed packages are reached.
+///
+/// Only the shortest chain to each manually installed package is kept.
+fn shortest_chains(pkg: &Package, dep_types: &[DepType]) -> Vec<WhyChain> {
+	let mut chains = vec![];
+	let mut seen = HashSet::from([pkg.fullname(true)]);
+	let mut queue = VecDeque::from([(pkg.clone(), vec![])]);
+
+	while let Some((current, links)) = queue.pop_front() {
+		let ver = current.installed().or_else(|| current.candidate());
+		for target in rdepends_targets(&current, ver) {
+			for dep_type in dep_types {
+				let Some(deps) = target.rdepends().get(dep_type) else {
+					continue;
+				};
+
+				for base_dep in deps.iter().flat_map(|dep| dep.iter()) {
+					let parent_ver = unsafe { base_dep.parent_ver() };
+					if !parent_ver.is_installed() {
+						continue;
+					}
+
+					let parent = base_dep.target_package();
+					if !seen.insert(parent.fullname(true)) {
+						continue;
+					}
+
+					let mut chain = vec![WhyLink {
+						package: parent.fullname(true),
+						version: parent_ver.version().to_string(),
+						dep_type: dep_type.clone(),
+						relation: relation(base_dep),
+					}];
+					chain.extend_from_slice(&links);
+
+					if parent.is_auto_installed() {
+						queue.push_back((parent.clone(), chain));
+					} else {
+						chains.push(WhyChain::new(chain));
+					}
+				}
+			}
+		}
+	}
+	chains
+}
+
+pub(crate) fn why(pkg: &Package) -> Vec<WhyChain> {
+	let mut by_root: HashMap<String, WhyChain> = HashMap::new();
+
+	// Search with only the strong dependencies first,
+	// so a longer Depends chain will win over a shorter Recommends one.
+	for dep_types in [
+		&[DepType::Depends, DepType::PreDepends][..],
+		&[DepType::Depends, DepType::PreDepends, DepType::Recommends],
+	] {
+		for chain in shortest_chains(pkg, dep_types) {
+			by_root.entry(chain.root().to_string()).or_insert(chain);
+		}
+	}
+
+	let mut chains: Vec<WhyChain> = by_root.into_values().collect();
+	chains.sort_by(|a, b| {
+		a.strength
+			.cmp(&b.strength)
+			.then_with(|| a.links.len().cmp(&b.links.len()))
+			.then_with(|| a.root().cmp(b.root()))
+	});
+	chains
+}
+
+pub(crate) fn why_not(pkg: &Package) -> Vec<WhyNot> {
+	let mut found = vec![];
+	let Some(cand) = pkg.candidate() else {
+		return found;
+	};
+	let name = pkg.fullname(true);
+
+	for dep_type in [DepType::Conflicts, DepType::DpkgBreaks] {
+		// Relationships declared by the candidate against installed packages.
+		for base_dep in cand
+			.get_depends(&dep_type)
+			.into_iter()
+			.flatten()
+			.flat_map(|dep| dep.iter())
+		{
+			for target in base_dep.all_targets() {
+				if !target.is_installed() || target.parent().index() == pkg.index() {
+					continue;
+				}
+				found.push(WhyNot {
+					package: target.parent().fullname(true),
+					version: target.version().to_string(),
+					dep_type: dep_type.clone(),
+					relation: relation(base_dep),
+					declared_by: name.clone(),
+				});
+			}
+		}
+
+		// Relationships declared by installed packages against the candidate.
+		for target in rdepends_targets(pkg, Some(cand.clone())) {
+			let Some(deps) = target.rdepends().get(&dep_type) else {
+				continue;
+			};
+
+			for base_dep in deps.iter().flat_map(|dep| dep.iter()) {
+				let parent_ver = unsafe { base_dep.parent_ver() };
+				let parent = base_dep.target_package();
+				if !parent_ver.is_installed() || parent == pkg {
+					continue;
+				}
+
+				if !base_dep
+					.all_targets()
+					.iter()
+					.any(|v| v.index() == cand.index())
+				{
+					continue;
+				}
+
+				found.push(WhyNot {
+					package: parent.fullname(true),
+					version: parent_ver.version().to_string(),
+					dep_type: dep_type.clone(),
+					relation: relation(base_dep),
+					declared_by: parent.fullname(true),
+				});
+			}
+		}
+	}
+	found
+}
@@ -1,12 +1,21 @@
+mod common;
+
 mod cache {
 	use std::collections::HashMap;
+	use std::ffi::OsStr;
 	use std::fmt::Write as _;
+	use std::io::Write as _;
+	use std::os::unix::ffi::OsStrExt;
+	use std::path::Path;
 
 	use cxx::{CxxVector, UniquePtr};
 	use oma_apt::cache::*;
+	use oma_apt::dpkg::{ConffileState, FileChange, ModifiedFile};
 	use oma_apt::raw::{create_acquire, IntoRawIter, ItemDesc};
 	use oma_apt::util::*;
-	use oma_apt::{new_cache, DepType};
+	use oma_apt::{new_cache, ArchQualifier, DepType, MultiArch, PkgSelectedState};
+
+	use crate::common::fixture_cache;
 
 	// This is a manual test. I don't know a good way to dynamically test this
 	// Maybe by installing a test-deb with certain depends and checking the
@@ -85,10 +94,82 @@ mod cache {
 		cache.get("dep-pkg1").unwrap().get_version("0.0.2").unwrap();
 		cache.get("dep-pkg2").unwrap();
 		cache.get("no-description").unwrap();
+	}
+
+	#[test]
+	fn compressed_packages() {
+		let dir = std::env::temp_dir().join("oma-apt-compressed-packages");
+		std::fs::create_dir_all(&dir).unwrap();
+		let packages = std::fs::read("tests/files/cache/Packages").unwrap();
+
+		let gz = dir.join("Packages.gz");
+		let mut encoder =
+			flate2::write::GzEncoder::new(std::fs::File::create(&gz).unwrap(), Default::default());
+		encoder.write_all(&packages).unwrap();
+		encoder.finish().unwrap();
+
+		let xz = dir.join("Packages.xz");
+		let mut encoder = xz2::write::XzEncoder::new(std::fs::File::create(&xz).unwrap(), 6);
+		encoder.write_all(&packages).unwrap();
+		encoder.finish().unwrap();
+
+		for file in [&gz, &xz] {
+			let cache = CacheBuilder::new()
+				.isolated()
+				.local_files(&[file.to_str().unwrap()])
+				.build()
+				.unwrap();
+
+			let pkg = cache.get("dep-pkg1").unwrap();
+			pkg.get_version("0.0.1").unwrap();
+			assert_eq!(pkg.candidate().unwrap().version(), "0.0.2");
+			assert!(cache.get("no-description").is_some());
+		}
+		std::fs::remove_dir_all(&dir).unwrap();
 
 		assert!(new_cache!(&["tests/files/cache/Packages.gz"]).is_err());
 	}
 
+	#[test]
+	fn local_sources() {
+		let dir = std::env::temp_dir().join("oma-apt-local-sources");
+		std::fs::create_dir_all(&dir).unwrap();
+		let sources = [
+			"Package: local-src",
+			"Binary: local-bin",
+			"Version: 1.2-3",
+			"Maintainer: oma-apt",
+			"Architecture: all",
+			"Section: misc",
+			"Directory: pool/main/l/local-src",
+			"Files:",
+			" 00000000000000000000000000000000 0 local-src_1.2-3.dsc",
+			"",
+		]
+		.join("\n");
+
+		let plain = dir.join("Sources");
+		std::fs::write(&plain, &sources).unwrap();
+		std::fs::create_dir_all(dir.join("zst")).unwrap();
+		let zst = dir.join("zst/Sources.zst");
+		std::fs::write(&zst, zstd::encode_all(sources.as_bytes(), 0).unwrap()).unwrap();
+
+		for file in [&plain, &zst] {
+			let cache = CacheBuilder::new()
+				.isolated()
+				.local_files(&[file.to_str().unwrap()])
+				.build()
+				.unwrap();
+
+			let records = cache.source_records().unwrap();
+			let record = records.lookup("local-src".to_string(), true).unwrap();
+			assert_eq!(record.package(), "local-src");
+			assert_eq!(record.version(), "1.2-3");
+			assert_eq!(record.section(), "misc");
+		}
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+
 	#[test]
 	fn empty_deps() {
 		// This would fail before https://gitlab.com/volian/oma-apt/-/merge_requests/29
@@ -167,6 +248,32 @@ mod cache {
 		dbg!(cand_desc);
 	}
 
+	#[test]
+	fn translated_descriptions() {
+		let cache = new_cache!().unwrap();
+		let cand = cache.get("apt").unwrap().candidate().unwrap();
+
+		// There is always at least the untranslated description
+		let descs: Vec<_> = cand.descriptions().collect();
+		assert!(descs.iter().any(|d| d.language().is_empty()));
+
+		// Every translation shares the md5 of the untranslated description
+		let md5 = descs.first().unwrap().md5().unwrap();
+		assert!(descs.iter().all(|d| d.md5().unwrap() == md5));
+
+		// "en" matches the untranslated description
+		let en = cand.description_for(&["en"]).unwrap();
+		assert!(en.language().is_empty() || en.language() == "en");
+		assert!(en.summary().is_some());
+
+		// A language that doesn't exist falls back to the untranslated one
+		let fallback = cand.description_for(&["not-a-language"]).unwrap();
+		assert_eq!(fallback.summary(), en.summary());
+
+		// Looking up a translation must not break version records.
+		assert_eq!(cand.get_record("Package").unwrap(), "apt");
+	}
+
 	// This should not segfault, but has in the past.
 	// See https://gitlab.com/volian/oma-apt/-/issues/28
 	#[test]
@@ -694,4 +801,341 @@ mod cache {
 		}
 		println!("{err}");
 	}
+
+	#[test]
+	fn broken_report() {
+		let cache = new_cache!(&["tests/files/cache/broken-or-dep_0.0.1.deb"]).unwrap();
+
+		let pkg = cache.get("broken-or-dep").unwrap();
+		pkg.protect();
+		pkg.mark_install(false, true);
+		cache.resolve(false).unwrap_err();
+
+		let report = BrokenReport::new(&cache, &pkg, false).unwrap();
+		assert_eq!(report.package, "broken-or-dep");
+		assert_eq!(report.version.as_deref(), Some("0.0.1"));
+		assert_eq!(report.groups.len(), 2);
+
+		let or_group = report
+			.groups
+			.iter()
+			.find(|g| g.alternatives.len() == 2)
+			.unwrap();
+		assert_eq!(or_group.dep_type, DepType::Depends);
+
+		let first = &or_group.alternatives[0];
+		assert_eq!(first.name, "not-exist");
+		assert_eq!(first.comp_type.as_deref(), Some(">="));
+		assert_eq!(first.version.as_deref(), Some("3.6.1"));
+		assert_eq!(first.reason, Some(BrokenReason::VirtualNoProvider));
+		assert_eq!(first.target_version, None);
+
+		assert_eq!(
+			report.to_string(),
+			concat!(
+				" broken-or-dep : Depends: not-exist (>= 3.6.1) but it is not installable or\n",
+				"                          really-not-exist but it is not installable\n",
+				"                 Depends: python3-not-exist but it is not installable\n",
+			)
+		);
+	}
+
+	#[test]
+	fn broken_report_version() {
+		let cache = fixture_cache("broken-version");
+
+		let app = cache.get("vm-app").unwrap();
+		app.mark_install(false, true);
+		cache.get("vm-lib").unwrap().mark_install(false, true);
+
+		let report = BrokenReport::new(&cache, &app, false).unwrap();
+		let lib = &report.groups[0].alternatives[0];
+		assert_eq!(lib.target_version.as_deref(), Some("1.0"));
+		assert_eq!(
+			lib.reason,
+			Some(BrokenReason::VersionMismatch("1.0".to_string()))
+		);
+
+		assert_eq!(
+			report.to_string(),
+			" vm-app : Depends: vm-lib (>= 2.0) but 1.0 is to be installed\n"
+		);
+	}
+
+	#[test]
+	fn selections() {
+		let cache = new_cache!().unwrap();
+
+		// apt is installed, so it must have a selection
+		let selections = cache.get_selections();
+		let apt = selections.iter().find(|s| s.package == "apt").unwrap();
+
+		// The names round trip like dpkg --get-selections
+		let state: PkgSelectedState = apt.state.to_str().parse().unwrap();
+		assert_eq!(state, apt.state);
+		assert!("not-a-state".parse::<PkgSelectedState>().is_err());
+
+		// Packages that don't exist are an error before anything is written
+		let mut bad = apt.clone();
+		bad.package = "not-a-real-package".to_string();
+		assert!(cache.set_selections(&[bad]).is_err());
+	}
+
+	#[test]
+	fn builder_root_dir() {
+		let root = std::env::temp_dir().join("oma-apt-builder-root");
+		for dir in [
+			"var/lib/dpkg",
+			"var/lib/apt/lists",
+			"etc/apt/sources.list.d",
+		] {
+			std::fs::create_dir_all(root.join(dir)).unwrap();
+		}
+		std::fs::write(root.join("etc/apt/sources.list"), "").unwrap();
+		std::fs::write(
+			root.join("var/lib/dpkg/status"),
+			[
+				"Package: oma-apt-chroot",
+				"Status: install ok installed",
+				"Version: 1.0",
+				"Architecture: all",
+				"Maintainer: oma-apt",
+				"Description: A package in a chroot",
+				"",
+			]
+			.join("\n"),
+		)
+		.unwrap();
+
+		let config = oma_apt::config::Config::new();
+		let arch = config.find("APT::Architecture", "");
+
+		let cache = CacheBuilder::new()
+			.root_dir(&root)
+			.architectures(&["riscv64", "amd64"])
+			.build()
+			.unwrap();
+		std::fs::remove_dir_all(&root).unwrap();
+
+		let pkg = cache.get("oma-apt-chroot").unwrap();
+		assert!(pkg.is_installed());
+		// The architectures of the builder are used, not the ones of the host
+		assert_eq!(cache.native_arch(), "riscv64");
+		assert!(pkg.is_native());
+		let ver = pkg.installed().unwrap();
+		assert!(ver.satisfies_arch(&ArchQualifier::None, "riscv64"));
+		assert!(ver.satisfies_arch(&ArchQualifier::Native, "amd64"));
+		// Only the chroot is read
+		assert!(cache.get("apt").is_none());
+		assert_eq!(cache.root_dir(), root);
+		assert!(cache.admin_dir().starts_with(&root));
+
+		// The configuration for everyone else is the same as before
+		assert_eq!(config.find("APT::Architecture", ""), arch);
+		assert!(config.get("RootDir").is_none());
+		assert!(new_cache!().unwrap().get("apt").is_some());
+	}
+
+	#[test]
+	fn builder_isolated() {
+		let status = std::env::temp_dir().join("oma-apt-isolated-status");
+		std::fs::write(
+			&status,
+			[
+				"Package: dep-pkg1",
+				"Status: install ok installed",
+				"Version: 0.0.1",
+				"Architecture: all",
+				"Maintainer: oma-apt",
+				"Description: Installed in the snapshot",
+				"",
+			]
+			.join("\n"),
+		)
+		.unwrap();
+
+		let cache = CacheBuilder::new()
+			.isolated()
+			.status(&status)
+			.local_files(&["tests/files/cache/Packages"])
+			.build()
+			.unwrap();
+		std::fs::remove_file(&status).unwrap();
+
+		// Only the status file is installed
+		let installed: Vec<_> = cache
+			.packages(&PackageSort::default().installed())
+			.map(|pkg| pkg.name().to_string())
+			.collect();
+		assert_eq!(installed, vec!["dep-pkg1"]);
+
+		let pkg = cache.get("dep-pkg1").unwrap();
+		assert_eq!(pkg.installed().unwrap().version(), "0.0.1");
+		assert_eq!(pkg.candidate().unwrap().version(), "0.0.2");
+
+		// Nothing from the system sources
+		assert!(cache.get("dpkg").is_none());
+	}
+
+	#[test]
+	fn multi_arch_lookup() {
+		let cache = new_cache!().unwrap();
+		let apt = cache.get("apt").unwrap();
+
+		assert!(apt.is_native());
+		assert!(!apt.is_foreign());
+
+		let group = cache.group("apt");
+		assert!(group.contains(&apt));
+		assert_eq!(apt.group().len(), group.len());
+		for pkg in &group {
+			assert_eq!(pkg.name(), "apt");
+		}
+
+		assert_eq!(cache.get_arch("apt", "native").unwrap(), apt);
+		assert_eq!(cache.get_arch("apt", apt.arch()).unwrap(), apt);
+		assert!(cache.get_arch("apt", "not-an-arch").is_none());
+		assert!(cache.group("not-a-real-package").is_empty());
+
+		let cache = CacheBuilder::new()
+			.isolated()
+			.local_files(&["tests/files/cache/Packages"])
+			.build()
+			.unwrap();
+		let pkg = cache.get("dep-pkg1").unwrap();
+		assert!(pkg.is_arch_all());
+		assert!(pkg.is_native());
+	}
+
+	#[test]
+	fn multi_arch_satisfies() {
+		let none = ArchQualifier::None;
+		let any = ArchQualifier::Any;
+		let native = ArchQualifier::Native;
+
+		// Cross building riscv64 packages on amd64
+		let (arch, from, build) = ("amd64", "riscv64", "amd64");
+
+		assert!(!MultiArch::None.satisfies(&none, arch, from, build));
+		assert!(MultiArch::None.satisfies(&none, from, from, build));
+		assert!(MultiArch::Foreign.satisfies(&none, arch, from, build));
+		assert!(MultiArch::AllForeign.satisfies(&none, "all", from, build));
+		assert!(!MultiArch::Same.satisfies(&none, arch, from, build));
+
+		assert!(MultiArch::Allowed.satisfies(&any, arch, from, build));
+		assert!(MultiArch::AllAllowed.satisfies(&any, "all", from, build));
+		assert!(!MultiArch::Foreign.satisfies(&any, arch, from, build));
+		assert!(!MultiArch::None.satisfies(&any, from, from, build));
+
+		assert!(MultiArch::None.satisfies(&native, arch, from, build));
+		assert!(!MultiArch::None.satisfies(&native, from, from, build));
+		assert!(MultiArch::All.satisfies(&native, "all", from, build));
+
+		let i386 = ArchQualifier::Arch("i386".to_string());
+		assert!(MultiArch::Same.satisfies(&i386, "i386", arch, build));
+		assert_eq!(ArchQualifier::split("libc6:i386"), ("libc6", i386));
+
+		assert_eq!(MultiArch::from(9), MultiArch::AllAllowed);
+		assert!(MultiArch::AllAllowed.is_all());
+		assert!(MultiArch::AllAllowed.is_allowed());
+
+		let cache = new_cache!().unwrap();
+		let apt = cache.get("apt").unwrap().candidate().unwrap();
+		assert!(apt.satisfies_arch(&ArchQualifier::None, apt.arch()));
+	}
+
+	/// The system in `tests/files/chroot`.
+	fn chroot_cache() -> Cache {
+		CacheBuilder::new()
+			.root_dir("tests/files/chroot")
+			.isolated()
+			.status("/var/lib/dpkg/status")
+			.build()
+			.unwrap()
+	}
+
+	#[test]
+	fn conffiles() {
+		let cache = new_cache!().unwrap();
+		let apt = cache.get("apt").unwrap();
+
+		let conffiles = apt.conffiles();
+		assert!(!conffiles.is_empty());
+		for conffile in &conffiles {
+			assert!(conffile.path.starts_with('/'));
+			println!("{} {:?}", conffile.path, conffile.state());
+		}
+
+		// Packages that aren't installed don't have any.
+		let not_installed = PackageSort::default().not_installed();
+		let pkg = cache.packages(&not_installed).next();
+		assert!(pkg.unwrap().conffiles().is_empty());
+
+		// The files are compared under the root of the cache.
+		let cache = chroot_cache();
+		let pkg = cache.get("oma-apt-conffiles").unwrap();
+
+		let conffiles = pkg.conffiles();
+		let states: Vec<_> = conffiles
+			.iter()
+			.map(|c| (c.path.as_str(), c.state()))
+			.collect();
+		assert_eq!(
+			states,
+			[
+				("/etc/oma-apt/kept.conf", ConffileState::Unmodified),
+				("/etc/oma-apt/changed.conf", ConffileState::Modified),
+				("/etc/oma-apt/removed.conf", ConffileState::Missing),
+				("/etc/oma-apt/old.conf", ConffileState::Missing),
+			]
+		);
+
+		// Obsolete conffiles aren't changed ones.
+		let changed = pkg.changed_conffiles();
+		let changed: Vec<_> = changed.iter().map(|c| c.path.as_str()).collect();
+		assert_eq!(
+			changed,
+			["/etc/oma-apt/changed.conf", "/etc/oma-apt/removed.conf"]
+		);
+	}
+
+	#[test]
+	fn dpkg_files() {
+		let cache = new_cache!().unwrap();
+		let apt = cache.get("apt").unwrap();
+
+		let files = apt.installed_files().unwrap();
+		assert!(files.iter().any(|f| f == "/usr/bin/apt"));
+
+		let owners = cache.owner_of("/usr/bin/apt").unwrap();
+		assert_eq!(owners, vec![apt.clone()]);
+		assert!(cache.owner_of("/not/a/real/file").unwrap().is_empty());
+
+		for file in apt.verify_files().unwrap() {
+			println!("{} {:?}", file.path.display(), file.change);
+		}
+
+		// libc6 is Multi-Arch: same, so its list has the arch in the name.
+		let libc = cache.get("libc6").unwrap();
+		assert!(!libc.installed_files().unwrap().is_empty());
+
+		let not_installed = PackageSort::default().not_installed();
+		let pkg = cache.packages(&not_installed).next();
+		assert!(pkg.unwrap().installed_files().unwrap().is_empty());
+
+		// The names don't have to be UTF-8.
+		let cache = chroot_cache();
+		let pkg = cache.get("oma-apt-conffiles").unwrap();
+		let cafe = Path::new(OsStr::from_bytes(b"/usr/share/oma-apt/caf\xe9"));
+
+		assert!(pkg.installed_files().unwrap().iter().any(|f| f == cafe));
+		assert_eq!(cache.owner_of(cafe).unwrap(), vec![pkg.clone()]);
+		assert_eq!(
+			pkg.verify_files().unwrap(),
+			[ModifiedFile {
+				path: cafe.to_path_buf(),
+				change: FileChange::Missing,
+			}]
+		);
+	}
 }
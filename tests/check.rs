@@ -0,0 +1,66 @@
+mod common;
+
+mod check {
+	use oma_apt::check::InstallCheck;
+	use oma_apt::util::BrokenReason;
+
+	use crate::common::fixture_cache;
+
+	#[test]
+	fn snapshot() {
+		let cache = fixture_cache("cache");
+
+		let bad = cache.check_installable(&InstallCheck::new()).unwrap();
+		let names: Vec<_> = bad
+			.iter()
+			.map(|b| b.version.parent().name().to_string())
+			.collect();
+
+		// Nothing else is in the snapshot, so the dependencies are missing.
+		assert!(names.contains(&"dep-pkg1".to_string()));
+		assert!(!names.contains(&"apt".to_string()));
+
+		let dep_pkg = bad
+			.iter()
+			.find(|b| b.version.parent().name() == "dep-pkg1")
+			.unwrap();
+		let report = &dep_pkg.broken[0];
+		assert_eq!(report.package, "dep-pkg1");
+		assert!(report.groups.iter().any(|group| {
+			group.alternatives.iter().any(|dep| {
+				dep.name == "htop" && dep.reason == Some(BrokenReason::VirtualNoProvider)
+			})
+		}));
+
+		// Every version is checked unless only candidates are asked for.
+		let candidates = cache
+			.check_installable(&InstallCheck::new().candidates_only())
+			.unwrap();
+		assert!(candidates.len() < bad.len());
+
+		// Nothing is left marked
+		assert!(cache.get_changes(false).next().is_none());
+
+		// The local file isn't part of any named archive.
+		let stable = cache
+			.check_installable(&InstallCheck::new().archive("oma-apt-none"))
+			.unwrap();
+		assert!(stable.is_empty());
+	}
+
+	#[test]
+	fn swapped_breakage() {
+		// check-a is already broken, and the held check-c can't make way.
+		let cache = fixture_cache("check-swapped");
+
+		// As many packages are broken as before, but not the same ones.
+		let bad = cache.check_installable(&InstallCheck::new()).unwrap();
+		assert_eq!(bad.len(), 1);
+		assert_eq!(bad[0].version.parent().name(), "check-b");
+		let reported: Vec<&str> = bad[0].broken.iter().map(|r| r.package.as_str()).collect();
+		assert!(reported.contains(&"check-c"));
+
+		assert!(cache.get_changes(false).next().is_none());
+		assert!(cache.get("check-a").unwrap().is_inst_broken());
+	}
+}
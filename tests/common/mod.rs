@@ -0,0 +1,24 @@
+//! Helpers that are shared by the tests.
+use std::path::Path;
+
+use oma_apt::cache::CacheBuilder;
+use oma_apt::Cache;
+
+/// Open a cache of only the fixture in `tests/files/<name>`.
+///
+/// The fixture has a `Packages` file, a dpkg `status` file or both. Nothing
+/// is read from the system, so it doesn't matter what is installed.
+pub fn fixture_cache(name: &str) -> Cache {
+	let dir = Path::new("tests/files").join(name);
+	let mut builder = CacheBuilder::new().isolated();
+
+	let status = dir.join("status");
+	if status.exists() {
+		builder = builder.status(status);
+	}
+	let packages = dir.join("Packages");
+	if packages.exists() {
+		builder = builder.local_files(&[packages.to_string_lossy()]);
+	}
+	builder.build().unwrap()
+}
@@ -2,7 +2,7 @@ mod config {
 	use std::collections::VecDeque;
 	use std::process::Command;
 
-	use oma_apt::config::Config;
+	use oma_apt::config::{Config, ConfigScope};
 	use oma_apt::new_cache;
 
 	#[test]
@@ -154,4 +154,31 @@ mod config {
 			}
 		}
 	}
+
+	#[test]
+	fn scope() {
+		let config = Config::new();
+		let value = "oma_apt::scope::Value";
+		let list = "oma_apt::scope::List";
+		let missing = "oma_apt::scope::Missing";
+
+		config.set(value, "before");
+		config.set_vector(list, &vec!["one", "two"]);
+
+		{
+			let mut scope = ConfigScope::new();
+			scope.set(value, "during");
+			scope.set(value, "again");
+			scope.set_vector(list, &vec!["three"]);
+			scope.set(missing, "during");
+
+			assert_eq!(config.find(value, ""), "again");
+			assert_eq!(config.find_vector(list), vec!["three"]);
+			assert_eq!(config.find(missing, ""), "during");
+		}
+
+		assert_eq!(config.find(value, ""), "before");
+		assert_eq!(config.find_vector(list), vec!["one", "two"]);
+		assert!(config.get(missing).is_none());
+	}
 }
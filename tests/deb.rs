@@ -0,0 +1,111 @@
+#[cfg(feature = "deb")]
+mod deb {
+	use std::fs;
+	use std::os::unix::fs::PermissionsExt;
+
+	use oma_apt::deb::{Compression, DebBuilder, DebFile};
+	use oma_apt::new_cache;
+	use oma_apt::tagfile::TagSection;
+
+	#[test]
+	fn read_deb() {
+		let deb = DebFile::open("tests/files/cache/apt.deb").unwrap();
+
+		assert!(deb.format().starts_with("2."));
+		assert_eq!(deb.control().get("Package").unwrap(), "apt");
+		assert_eq!(deb.control().get("Version").unwrap(), "5000:1.0.0");
+		assert_eq!(deb.maintainer_scripts().count(), 0);
+		assert!(deb.conffiles().is_empty());
+
+		let names: Vec<String> = deb
+			.control_tar()
+			.unwrap()
+			.entries()
+			.unwrap()
+			.map(|entry| entry.unwrap().path().unwrap().display().to_string())
+			.collect();
+		assert!(names.iter().any(|name| name.ends_with("control")));
+
+		// The data tar is only the root directory.
+		assert!(deb.data_tar().unwrap().entries().unwrap().count() >= 1);
+
+		assert!(DebFile::open("tests/files/cache/pkg.deb").is_err());
+		assert!(DebFile::open("tests/files/this-file-doesnt-exist.deb").is_err());
+	}
+
+	#[test]
+	fn build_deb() {
+		let dir = std::env::temp_dir().join("oma-apt-build-deb");
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(dir.join("staging/DEBIAN")).unwrap();
+		fs::create_dir_all(dir.join("staging/etc")).unwrap();
+		fs::write(dir.join("staging/etc/built.conf"), "hello").unwrap();
+		fs::write(dir.join("staging/DEBIAN/conffiles"), "/etc/built.conf\n").unwrap();
+		fs::write(dir.join("staging/DEBIAN/postinst"), "#!/bin/sh\n").unwrap();
+		fs::set_permissions(
+			dir.join("staging/DEBIAN/postinst"),
+			fs::Permissions::from_mode(0o755),
+		)
+		.unwrap();
+
+		let control = TagSection::new(
+			"Package: built-pkg\nVersion: 1.0\nArchitecture: all\nDepends: apt\nMaintainer: Foo \
+			 Bar <foobar@example.com>\nDescription: A built package",
+		)
+		.unwrap();
+
+		for compression in [
+			Compression::None,
+			Compression::Gzip,
+			Compression::Xz,
+			Compression::Zstd,
+		] {
+			let path = dir.join(format!("built-pkg{}.deb", compression.extension()));
+			DebBuilder::new(control.clone(), dir.join("staging"))
+				.compression(compression)
+				.control_file("prerm", b"#!/bin/sh\n")
+				.build(&path)
+				.unwrap();
+
+			let deb = DebFile::open(&path).unwrap();
+			assert_eq!(deb.control().get("Package").unwrap(), "built-pkg");
+			// Each of the 3 directories and 3 small files is 1 KiB.
+			assert_eq!(deb.control().get("Installed-Size").unwrap(), "6");
+			assert_eq!(deb.conffiles(), ["/etc/built.conf"]);
+			assert!(
+				deb.maintainer_scripts()
+					.all(|script| script.is_executable())
+			);
+			assert_eq!(deb.maintainer_scripts().count(), 2);
+			assert_eq!(
+				String::from_utf8_lossy(&deb.control_file("md5sums").unwrap().content),
+				"5d41402abc4b2a76b9719d911017c592  etc/built.conf\n"
+			);
+
+			let names: Vec<String> = deb
+				.data_tar()
+				.unwrap()
+				.entries()
+				.unwrap()
+				.map(|entry| entry.unwrap().path().unwrap().display().to_string())
+				.collect();
+			assert!(names.contains(&"./etc/built.conf".to_string()));
+			assert!(!names.iter().any(|name| name.contains("DEBIAN")));
+
+			let cache = new_cache!(&[path.to_str().unwrap()]).unwrap();
+			let pkg = cache.get("built-pkg").unwrap();
+			assert_eq!(pkg.candidate().unwrap().version(), "1.0");
+		}
+
+		// The control stanza needs the fields apt uses to identify the package.
+		let mut incomplete = control.clone();
+		incomplete.remove("Version");
+		assert!(
+			DebBuilder::new(incomplete, dir.join("staging"))
+				.build(dir.join("incomplete.deb"))
+				.is_err()
+		);
+
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
@@ -1,6 +1,11 @@
+mod common;
+
 mod depcache {
-	use oma_apt::cache::Upgrade;
+	use oma_apt::cache::{CacheBuilder, Upgrade};
 	use oma_apt::new_cache;
+	use oma_apt::progress::OperationProgress;
+
+	use crate::common::fixture_cache;
 
 	#[test]
 	fn mark_reinstall() {
@@ -25,6 +30,174 @@ mod depcache {
 		action_group.pin_mut().release();
 	}
 
+	#[test]
+	fn action_guard() {
+		let cache = new_cache!().unwrap();
+
+		// Dropping the cache while the guard is alive won't compile.
+		let guard = cache.depcache().action_guard();
+		cache.get("apt").unwrap().mark_reinstall(true);
+		guard.release();
+
+		// The guard is released when it leaves scope
+		{
+			let _guard = cache.depcache().action_guard();
+			cache.get("apt").unwrap().mark_reinstall(false);
+		}
+
+		let reinstall = cache.depcache().batch(|| {
+			let pkg = cache.get("apt").unwrap();
+			pkg.mark_reinstall(true);
+			pkg.marked_reinstall()
+		});
+		assert!(reinstall);
+	}
+
+	#[test]
+	fn savepoint() {
+		let cache = new_cache!(&["tests/files/cache/Packages"]).unwrap();
+		let depcache = cache.depcache();
+
+		let apt = cache.get("apt").unwrap();
+		let dep_pkg = cache.get("dep-pkg1").unwrap();
+		let cand = dep_pkg.candidate().unwrap().version().to_string();
+		let auto = apt.is_auto_installed();
+
+		let save = depcache.savepoint();
+
+		// Make a bunch of changes
+		apt.mark_delete(false);
+		apt.mark_auto(!auto);
+		dep_pkg.get_version("0.0.1").unwrap().set_candidate();
+		dep_pkg.mark_install(true, true);
+		assert!(cache.get_changes(false).next().is_some());
+
+		depcache.restore(&save).unwrap();
+
+		// Everything should be back to how it was
+		assert!(cache.get_changes(false).next().is_none());
+		assert!(apt.marked_keep());
+		assert_eq!(apt.is_auto_installed(), auto);
+		assert!(!dep_pkg.marked_install());
+		assert_eq!(dep_pkg.candidate().unwrap().version(), cand);
+		assert_eq!(depcache.install_count(), 0);
+		assert_eq!(depcache.delete_count(), 0);
+
+		// A savepoint can be restored more than once
+		apt.mark_delete(false);
+		depcache.restore(&save).unwrap();
+		assert!(apt.marked_keep());
+
+		// A savepoint only belongs to its own cache, even if another has the
+		// same packages.
+		let other = new_cache!(&["tests/files/cache/Packages"]).unwrap();
+		assert!(other.depcache().restore(&save).is_err());
+	}
+
+	#[test]
+	fn plan_upgrade() {
+		let cache = new_cache!().unwrap();
+
+		for upgrade in [Upgrade::FullUpgrade, Upgrade::Upgrade, Upgrade::SafeUpgrade] {
+			let summary = cache.plan_upgrade(upgrade, true).unwrap();
+			assert!(summary.held_back.len() as u32 <= summary.kept);
+
+			for held in &summary.held_back {
+				assert!(held.package.is_upgradable());
+				println!("{} held back: {:?}", held.package.name(), held.reason);
+			}
+
+			// A dry run doesn't leave anything marked
+			assert!(cache.get_changes(false).next().is_none());
+		}
+
+		// Without a dry run the changes stay
+		let summary = cache.plan_upgrade(Upgrade::FullUpgrade, false).unwrap();
+		let changes = cache.get_changes(false).count() as u32;
+		assert!(changes >= summary.install + summary.upgrade + summary.remove);
+	}
+
+	#[test]
+	fn resolve_result() {
+		let cache = new_cache!(&["tests/files/cache/Packages"]).unwrap();
+		let mut progress = OperationProgress::quiet();
+
+		let pkg = cache.get("dep-pkg1").unwrap();
+		pkg.mark_install(false, true);
+		pkg.protect();
+
+		let result = cache.resolve_with_progress(false, &mut progress).unwrap();
+		assert!(pkg.marked_install());
+		assert!(result.kept_back.is_empty());
+		for new in &result.installed {
+			assert!(new.marked_install());
+		}
+
+		// Nothing is broken so keeping doesn't change anything
+		let result = cache.resolve_by_keep(&mut progress).unwrap();
+		assert!(result.is_empty());
+		assert!(pkg.marked_install());
+	}
+
+	#[test]
+	fn resolve_keep_back() {
+		let cache = fixture_cache("cache");
+		let mut progress = OperationProgress::quiet();
+
+		// None of its dependencies exist, so it can't be installed
+		let pkg = cache.get("broken-or-dep").unwrap();
+		pkg.mark_install(true, true);
+		assert!(pkg.is_inst_broken());
+
+		let result = cache.resolve_with_progress(false, &mut progress).unwrap();
+		assert!(!pkg.marked_install());
+		assert_eq!(result.kept_back, vec![pkg]);
+		assert!(result.removed.is_empty());
+	}
+
+	#[test]
+	fn resolve_remove() {
+		let open = || fixture_cache("resolve-remove");
+		let mut progress = OperationProgress::quiet();
+
+		// The removal is requested, so the app that needs it goes as well
+		let cache = open();
+		let lib = cache.get("rm-lib").unwrap();
+		lib.mark_delete(false);
+		lib.protect();
+		lib.resolver_remove();
+
+		let result = cache.resolve_with_progress(false, &mut progress).unwrap();
+		assert!(lib.marked_delete());
+		assert_eq!(result.removed, vec![cache.get("rm-app").unwrap()]);
+		assert!(result.not_removed.is_empty());
+
+		// Keeping the app instead undoes the removal
+		let cache = open();
+		let lib = cache.get("rm-lib").unwrap();
+		lib.mark_delete(false);
+		cache.get("rm-app").unwrap().protect();
+
+		let result = cache.resolve_with_progress(false, &mut progress).unwrap();
+		assert!(!lib.marked_delete());
+		assert_eq!(result.not_removed, vec![lib]);
+		assert!(result.removed.is_empty());
+		assert!(!result.is_empty());
+	}
+
+	#[test]
+	fn write_state_file_isolated() {
+		let cache = CacheBuilder::new()
+			.isolated()
+			.local_files(&["tests/files/cache/Packages"])
+			.build()
+			.unwrap();
+
+		// There is nowhere to write the marks to
+		cache.get("dep-pkg1").unwrap().mark_auto(true);
+		assert!(cache.depcache().write_state_file().is_err());
+	}
+
 	// Make a test for getting the candidate after you set a candidate.
 	// Make sure it's the expected version.
 	// We had to change to getting the candidate from the depcache.
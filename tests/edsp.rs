@@ -0,0 +1,59 @@
+mod edsp {
+	use std::fs;
+	use std::process::Command;
+
+	use oma_apt::edsp::RequestFlags;
+	use oma_apt::new_cache;
+	use oma_apt::progress::OperationProgress;
+
+	#[test]
+	fn write_request() {
+		let cache = new_cache!(&["tests/files/cache/Packages"]).unwrap();
+		cache.get("dep-pkg1").unwrap().mark_install(false, true);
+
+		let path = std::env::temp_dir().join("oma-apt-edsp-request");
+		cache
+			.write_edsp_request(&path, RequestFlags::ForbidRemove)
+			.unwrap();
+
+		let request = fs::read_to_string(&path).unwrap();
+		fs::remove_file(&path).unwrap();
+
+		assert!(request.starts_with("Request: EDSP"));
+		assert!(request.contains("Install: dep-pkg1"));
+		assert!(request.contains("Forbid-Remove: yes"));
+		assert!(request.contains("Package: dep-pkg1"));
+	}
+
+	#[test]
+	fn solver_answers() {
+		let cache = new_cache!().unwrap();
+		let mut progress = OperationProgress::quiet();
+
+		// An empty answer is valid and doesn't change anything.
+		let mut solver = Command::new("sh");
+		solver.args(["-c", "cat > /dev/null"]);
+		cache.resolve_external(&mut solver, 0, &mut progress).unwrap();
+		assert!(cache.get_changes(false).next().is_none());
+
+		let mut solver = Command::new("sh");
+		solver.args(["-c", "cat > /dev/null; exit 1"]);
+		assert!(cache.resolve_external(&mut solver, 0, &mut progress).is_err());
+
+		let mut solver = Command::new("oma-apt-solver-that-does-not-exist");
+		assert!(cache.resolve_external(&mut solver, 0, &mut progress).is_err());
+	}
+
+	#[test]
+	fn solver_error() {
+		let cache = new_cache!().unwrap();
+
+		let path = std::env::temp_dir().join("oma-apt-edsp-error");
+		fs::write(&path, "Error: oma-apt-test\nMessage: The solver gave up\n").unwrap();
+
+		let err = cache.read_edsp_response(&path).unwrap_err();
+		fs::remove_file(&path).unwrap();
+
+		assert!(err.to_string().contains("The solver gave up"));
+	}
+}
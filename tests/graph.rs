@@ -0,0 +1,106 @@
+mod common;
+
+mod graph {
+	use oma_apt::graph::{DependencyGraph, Direction, GraphOptions};
+	use oma_apt::{new_cache, DepType};
+
+	use crate::common::fixture_cache;
+
+	#[test]
+	fn forward() {
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("apt").unwrap();
+
+		let graph = DependencyGraph::new(&pkg, &GraphOptions::default());
+		assert_eq!(graph.direction(), Direction::Forward);
+		assert_eq!(graph.root().name, "apt");
+
+		// apt depends on libapt-pkg, so it must be a direct dependency
+		let edges = &graph.root().edges;
+		assert!(edges.iter().any(|e| e.target.starts_with("libapt-pkg")));
+
+		// Every edge should point to a node in the graph
+		for node in graph.nodes() {
+			for edge in &node.edges {
+				assert!(graph.get(&edge.target).is_some());
+				assert!(matches!(
+					edge.dep_type,
+					DepType::Depends | DepType::PreDepends
+				));
+			}
+		}
+
+		// libc6 depends on libgcc which depends on libc6
+		dbg!(graph.cycles());
+	}
+
+	#[test]
+	fn cycles() {
+		// cyc-a -> cyc-b -> cyc-c -> cyc-a is a cycle, cyc-d and cyc-e are one
+		// as well. cyc-c also depends on cyc-d, which doesn't join the cycles.
+		let cache = fixture_cache("graph-cycles");
+
+		let pkg = cache.get("cyc-a").unwrap();
+		let graph = DependencyGraph::new(&pkg, &GraphOptions::default());
+		assert_eq!(graph.nodes().len(), 6);
+
+		let mut cycles = graph.cycles();
+		cycles.sort();
+		assert_eq!(
+			cycles,
+			vec![vec!["cyc-a", "cyc-b", "cyc-c"], vec!["cyc-d", "cyc-e"]]
+		);
+	}
+
+	#[test]
+	fn depth() {
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("apt").unwrap();
+
+		let graph = DependencyGraph::new(&pkg, &GraphOptions::default().max_depth(1));
+		assert!(graph.nodes().iter().all(|n| n.depth <= 1));
+		let mut leaves = graph.nodes().iter().filter(|n| n.depth == 1);
+		assert!(leaves.all(|n| n.edges.is_empty()));
+
+		let graph = DependencyGraph::new(&pkg, &GraphOptions::default().max_depth(0));
+		assert_eq!(graph.nodes().len(), 1);
+	}
+
+	#[test]
+	fn reverse() {
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("libapt-pkg6.0").unwrap();
+
+		let options = GraphOptions::default()
+			.reverse()
+			.installed_only()
+			.max_depth(1);
+		let graph = DependencyGraph::new(&pkg, &options);
+
+		// apt is installed and depends on libapt-pkg
+		assert!(graph.get("apt").is_some());
+
+		let dot = graph.to_dot();
+		assert!(dot.starts_with("digraph"));
+		// Arrows point at what is depended on
+		assert!(dot.contains("\"apt\" -> \"libapt-pkg6.0\""));
+	}
+
+	#[test]
+	fn providers() {
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("apt").unwrap();
+
+		let options = GraphOptions::default()
+			.expand_providers()
+			.dep_types(&[DepType::Depends, DepType::PreDepends, DepType::Recommends])
+			.max_depth(1);
+		let graph = DependencyGraph::new(&pkg, &options);
+
+		// Virtual packages have been replaced by their providers
+		for edge in &graph.root().edges {
+			let target = cache.get(&edge.target).unwrap();
+			assert!(target.has_versions());
+		}
+	}
+}
@@ -0,0 +1,101 @@
+mod progress {
+	use std::io::Cursor;
+
+	use oma_apt::progress::{ConffilePrompt, InstallAction, InstallEvent, InstallEvents};
+
+	#[test]
+	fn install_events() {
+		assert_eq!(
+			InstallEvent::parse_line("pmstatus:apt:amd64:50:Unpacking apt (amd64)\n").unwrap(),
+			InstallEvent::Status {
+				package: "apt:amd64".to_string(),
+				percent: 50.0,
+				action: InstallAction::Unpacking,
+				message: "Unpacking apt (amd64)".to_string(),
+			}
+		);
+
+		assert_eq!(
+			InstallEvent::parse_line("pmstatus:dpkg-exec:16.6667:Running dpkg").unwrap(),
+			InstallEvent::Status {
+				package: "dpkg-exec".to_string(),
+				percent: 16.6667,
+				action: InstallAction::RunningDpkg,
+				message: "Running dpkg".to_string(),
+			}
+		);
+
+		assert_eq!(
+			InstallEvent::parse_line("pmerror:apt:amd64:75:subprocess returned error: 1").unwrap(),
+			InstallEvent::Error {
+				package: "apt:amd64".to_string(),
+				percent: 75.0,
+				message: "subprocess returned error: 1".to_string(),
+			}
+		);
+
+		assert_eq!(
+			InstallEvent::parse_line("pmconffile:/etc/foo:25:'/etc/foo' '/etc/foo.dpkg-new' 1 0")
+				.unwrap(),
+			InstallEvent::ConffilePrompt {
+				package: "/etc/foo".to_string(),
+				percent: 25.0,
+				prompt: ConffilePrompt {
+					current: "/etc/foo".to_string(),
+					new: "/etc/foo.dpkg-new".to_string(),
+					user_edited: true,
+					dist_edited: false,
+				},
+			}
+		);
+
+		assert!(InstallEvent::parse_line("dlstatus:1:0:Retrieving file 1 of 2").is_none());
+		assert!(InstallEvent::parse_line("pmstatus:apt").is_none());
+
+		for (message, action) in [
+			(
+				"Preparing to configure apt (amd64)",
+				InstallAction::PreparingConfigure,
+			),
+			("Preparing apt (amd64)", InstallAction::Preparing),
+			("Completely removing apt (amd64)", InstallAction::Purging),
+			("Removing apt (amd64)", InstallAction::Removing),
+			(
+				"Running post-installation trigger man-db (amd64)",
+				InstallAction::Triggers,
+			),
+			("Something else", InstallAction::Other),
+		] {
+			assert_eq!(InstallAction::from_message(message), action);
+		}
+
+		let lines = [
+			"dlstatus:1:0:Retrieving",
+			"pmstatus:apt:0:Installing apt",
+			"pmstatus:apt:50:Configuring apt",
+			"",
+		]
+		.join("\n");
+		let actions: Vec<InstallAction> = InstallEvents::new(Cursor::new(lines))
+			.map(|event| match event {
+				InstallEvent::Status { action, .. } => action,
+				_ => InstallAction::Other,
+			})
+			.collect();
+		assert_eq!(
+			actions,
+			[InstallAction::Installing, InstallAction::Configuring]
+		);
+
+		// A line that isn't UTF-8 doesn't end the events
+		let mut lines = b"pmstatus:apt:0:Unpacking /etc/caf\xe9\n".to_vec();
+		lines.extend_from_slice(b"pmstatus:apt:50:Configuring apt\n");
+		let messages: Vec<String> = InstallEvents::new(Cursor::new(lines))
+			.map(|event| match event {
+				InstallEvent::Status { message, .. } => message,
+				_ => String::new(),
+			})
+			.collect();
+		assert_eq!(messages, ["Unpacking /etc/caf\u{fffd}", "Configuring apt"]);
+	}
+}
@@ -0,0 +1,90 @@
+#[cfg(feature = "deb")]
+mod repo {
+	use std::fs;
+
+	use oma_apt::config::ConfigScope;
+	use oma_apt::deb::DebBuilder;
+	use oma_apt::new_cache;
+	use oma_apt::progress::AcquireProgress;
+	use oma_apt::repo::RepoBuilder;
+	use oma_apt::tagfile::{parse_tagfile, TagSection};
+	use oma_apt::util::hash_file;
+
+	#[test]
+	fn build_repo() {
+		let dir = std::env::temp_dir().join("oma-apt-repo");
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(dir.join("repo/pool")).unwrap();
+		fs::create_dir_all(dir.join("staging")).unwrap();
+		fs::create_dir_all(dir.join("lists/partial")).unwrap();
+
+		for (name, version) in [("repo-a", "1.0"), ("repo-a", "2.0"), ("repo-b", "1.0")] {
+			let control = TagSection::new(&format!(
+				"Package: {name}\nVersion: {version}\nArchitecture: all\nMaintainer: Foo Bar \
+				 <foobar@example.com>\nDescription: A repository package"
+			))
+			.unwrap();
+
+			DebBuilder::new(control, dir.join("staging"))
+				.build(dir.join(format!("repo/pool/{name}_{version}.deb")))
+				.unwrap();
+		}
+
+		// A stale signature would make apt reject the new Release.
+		fs::write(dir.join("repo/InRelease"), "stale").unwrap();
+		RepoBuilder::new(dir.join("repo"))
+			.origin("oma-apt")
+			.suite("testing")
+			.build()
+			.unwrap();
+		assert!(!dir.join("repo/InRelease").exists());
+
+		let packages = fs::read_to_string(dir.join("repo/Packages")).unwrap();
+		let stanzas = parse_tagfile(&packages).unwrap();
+		assert_eq!(stanzas.len(), 3);
+		assert_eq!(stanzas[0].get("Filename").unwrap(), "./pool/repo-a_1.0.deb");
+		assert_eq!(
+			stanzas[0].get("SHA256").unwrap(),
+			&hash_file(dir.join("repo/pool/repo-a_1.0.deb"), "SHA256").unwrap()
+		);
+
+		let release = fs::read_to_string(dir.join("repo/Release")).unwrap();
+		let release = TagSection::new(release.trim_end()).unwrap();
+		assert_eq!(release.get("Origin").unwrap(), "oma-apt");
+		assert_eq!(release.get("Suite").unwrap(), "testing");
+		assert!(release.get("Date").is_some());
+		for index in ["Packages", "Packages.xz"] {
+			let sha256 = hash_file(dir.join("repo").join(index), "SHA256").unwrap();
+			assert!(release.get("SHA256").unwrap().contains(&sha256));
+		}
+
+		// Read the repository the same way as any other.
+		fs::write(
+			dir.join("sources.list"),
+			format!("deb [trusted=yes] file:{} ./\n", dir.join("repo").display()),
+		)
+		.unwrap();
+
+		let mut scope = ConfigScope::new();
+		scope.set(
+			"Dir::Etc::sourcelist",
+			&dir.join("sources.list").to_string_lossy(),
+		);
+		scope.set("Dir::Etc::sourceparts", "/dev/null");
+		scope.set("Dir::State::lists", &dir.join("lists").to_string_lossy());
+		scope.set("Dir::Cache::pkgcache", "");
+		scope.set("Dir::Cache::srcpkgcache", "");
+
+		let cache = new_cache!().unwrap();
+		cache.update(&mut AcquireProgress::quiet()).unwrap();
+
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("repo-a").unwrap();
+		assert_eq!(pkg.candidate().unwrap().version(), "2.0");
+		assert_eq!(pkg.versions().count(), 2);
+		drop(cache);
+		drop(scope);
+
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
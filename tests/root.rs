@@ -1,9 +1,20 @@
 mod root {
+	#[cfg(feature = "deb")]
+	use std::fs;
+
+	use oma_apt::cache::CacheBuilder;
 	use oma_apt::config::Config;
-	use oma_apt::new_cache;
+	#[cfg(feature = "deb")]
+	use oma_apt::deb::DebBuilder;
+	#[cfg(feature = "deb")]
+	use oma_apt::dpkg::ConffileAnswer;
+	use oma_apt::dpkg::Selection;
 	use oma_apt::progress::{AcquireProgress, DynAcquireProgress, InstallProgress};
 	use oma_apt::raw::{AcqTextStatus, ItemDesc, ItemState, PkgAcquire};
+	#[cfg(feature = "deb")]
+	use oma_apt::tagfile::TagSection;
 	use oma_apt::util::*;
+	use oma_apt::{new_cache, PkgSelectedState};
 
 	#[test]
 	fn lock() {
@@ -155,4 +166,210 @@ mod root {
 
 		cache.commit(&mut progress, &mut inst_progress).unwrap();
 	}
+
+	#[test]
+	fn hold() {
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("apt").unwrap();
+		let before = pkg.selected_state();
+
+		pkg.hold().unwrap();
+
+		// The cache has to be reopened to see the change
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("apt").unwrap();
+		assert_eq!(pkg.selected_state(), PkgSelectedState::Hold);
+		let held = |s: &Selection| s.package == "apt" && s.state == PkgSelectedState::Hold;
+		assert!(cache.get_selections().iter().any(held));
+
+		pkg.unhold().unwrap();
+
+		let cache = new_cache!().unwrap();
+		assert_eq!(cache.get("apt").unwrap().selected_state(), before);
+	}
+
+	#[test]
+	fn write_state_file() {
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("apt").unwrap();
+		let auto = pkg.is_auto_installed();
+
+		pkg.mark_auto(!auto);
+		cache.depcache().write_state_file().unwrap();
+
+		// The mark should survive a new cache
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("apt").unwrap();
+		assert_eq!(pkg.is_auto_installed(), !auto);
+
+		// Leave no trace
+		pkg.mark_auto(auto);
+		cache.depcache().write_state_file().unwrap();
+		assert!(!apt_is_locked());
+	}
+
+	#[test]
+	fn write_state_file_root_dir() {
+		let root = std::env::temp_dir().join(format!("oma-apt-state-root-{}", std::process::id()));
+		for dir in ["var/lib/dpkg", "var/lib/apt"] {
+			std::fs::create_dir_all(root.join(dir)).unwrap();
+		}
+		std::fs::write(
+			root.join("var/lib/dpkg/status"),
+			[
+				"Package: oma-apt-chroot",
+				"Status: install ok installed",
+				"Version: 1.0",
+				"Architecture: all",
+				"Maintainer: oma-apt",
+				"Description: A package in a chroot",
+				"",
+			]
+			.join("\n"),
+		)
+		.unwrap();
+
+		let cache = CacheBuilder::new()
+			.root_dir(&root)
+			.source_list("/dev/null")
+			.source_parts("/dev/null")
+			.build()
+			.unwrap();
+		cache.get("oma-apt-chroot").unwrap().mark_auto(true);
+		cache.depcache().write_state_file().unwrap();
+
+		// The marks are written under the root, not on the host
+		let states = std::fs::read_to_string(root.join("var/lib/apt/extended_states")).unwrap();
+		assert!(states.contains("Package: oma-apt-chroot"));
+		assert!(new_cache!().unwrap().get("oma-apt-chroot").is_none());
+		assert!(!apt_is_locked());
+
+		std::fs::remove_dir_all(&root).unwrap();
+	}
+
+	#[test]
+	#[cfg(feature = "deb")]
+	fn conffile_prompts() {
+		let dir = std::env::temp_dir().join("oma-apt-conffile-prompts");
+		let conf = "/etc/oma-apt-conffile.conf";
+		let other = "/etc/oma-apt-conffile-other.conf";
+		let _ = fs::remove_dir_all(&dir);
+
+		let mut debs = vec![];
+		for version in ["1.0", "2.0"] {
+			let staging = dir.join(version);
+			fs::create_dir_all(staging.join("etc")).unwrap();
+			for path in [conf, other] {
+				fs::write(
+					staging.join(path.trim_start_matches('/')),
+					format!("{version}\n"),
+				)
+				.unwrap();
+			}
+
+			let control = TagSection::new(&format!(
+				"Package: oma-apt-conffile\nVersion: {version}\nArchitecture: all\nMaintainer: \
+				 Foo Bar <foobar@example.com>\nDescription: A package with a conffile"
+			))
+			.unwrap();
+
+			let deb = dir.join(format!("oma-apt-conffile_{version}.deb"));
+			DebBuilder::new(control, &staging)
+				.control_file("conffiles", format!("{conf}\n{other}\n").as_bytes())
+				.build(&deb)
+				.unwrap();
+			debs.push(deb.to_str().unwrap().to_string());
+		}
+
+		let mut progress = AcquireProgress::quiet();
+		let mut inst_progress = InstallProgress::apt();
+
+		let cache = new_cache!(&debs[..1]).unwrap();
+		let pkg = cache.get("oma-apt-conffile").unwrap();
+		pkg.mark_install(true, true);
+		cache.commit(&mut progress, &mut inst_progress).unwrap();
+
+		fs::write(conf, "edited\n").unwrap();
+
+		let cache = new_cache!(&debs[1..]).unwrap();
+		let pkg = cache.get("oma-apt-conffile").unwrap();
+		pkg.mark_install(true, true);
+
+		let mut asked = 0;
+		cache
+			.commit_with_conffiles(&mut progress, &mut inst_progress, |pkg, prompt, diff| {
+				asked += 1;
+				assert_eq!(pkg.name(), "oma-apt-conffile");
+				assert_eq!(prompt.current, conf);
+				match diff {
+					None => ConffileAnswer::ShowDiff,
+					Some(diff) => {
+						assert!(diff.contains("-edited") && diff.contains("+2.0"));
+						ConffileAnswer::Replace
+					},
+				}
+			})
+			.unwrap();
+
+		assert_eq!(asked, 2);
+		assert_eq!(fs::read_to_string(conf).unwrap(), "2.0\n");
+		assert_eq!(
+			fs::read_to_string(format!("{conf}.dpkg-old")).unwrap(),
+			"edited\n"
+		);
+		// The option is only set for that install.
+		let options = Config::new().find_vector("DPkg::Options");
+		assert!(!options.contains(&"--force-confnew".to_string()));
+
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("oma-apt-conffile").unwrap();
+		pkg.mark_delete(true);
+		cache.commit(&mut progress, &mut inst_progress).unwrap();
+		let _ = fs::remove_file(format!("{conf}.dpkg-old"));
+
+		// dpkg also asks when a new install finds a different file on disk.
+		// The answers are mixed, so only one is kept.
+		fs::write(conf, "mine\n").unwrap();
+		fs::write(other, "mine\n").unwrap();
+		let cache = new_cache!(&debs[1..]).unwrap();
+		let pkg = cache.get("oma-apt-conffile").unwrap();
+		pkg.mark_install(true, true);
+
+		let mut asked = 0;
+		cache
+			.commit_with_conffiles(&mut progress, &mut inst_progress, |_, prompt, _| {
+				asked += 1;
+				assert!(prompt.user_edited && prompt.dist_edited);
+				match prompt.current == conf {
+					true => ConffileAnswer::Keep,
+					false => ConffileAnswer::Replace,
+				}
+			})
+			.unwrap();
+
+		assert_eq!(asked, 2);
+		assert_eq!(fs::read_to_string(conf).unwrap(), "mine\n");
+		assert_eq!(
+			fs::read_to_string(format!("{conf}.dpkg-dist")).unwrap(),
+			"2.0\n"
+		);
+		assert_eq!(fs::read_to_string(other).unwrap(), "2.0\n");
+		assert_eq!(
+			fs::read_to_string(format!("{other}.dpkg-old")).unwrap(),
+			"mine\n"
+		);
+		let options = Config::new().find_vector("DPkg::Options");
+		assert!(!options.contains(&"--force-confold".to_string()));
+
+		// Leave no trace
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("oma-apt-conffile").unwrap();
+		pkg.mark_delete(true);
+		cache.commit(&mut progress, &mut inst_progress).unwrap();
+		let _ = fs::remove_file(conf);
+		let _ = fs::remove_file(format!("{conf}.dpkg-dist"));
+		let _ = fs::remove_file(other);
+		let _ = fs::remove_file(format!("{other}.dpkg-old"));
+		fs::remove_dir_all(&dir).unwrap();
+	}
 }
@@ -0,0 +1,49 @@
+mod scenario {
+	use std::fs;
+
+	use oma_apt::cache::{Cache, PackageSort};
+	use oma_apt::config::Config;
+	use oma_apt::new_cache;
+
+	#[test]
+	fn dump_and_load() {
+		let cache = new_cache!(&["tests/files/cache/Packages"]).unwrap();
+		let pkg = cache.get("dep-pkg1").unwrap();
+		pkg.mark_install(false, true);
+		pkg.protect();
+		cache.resolve(false).unwrap();
+
+		let installed: Vec<String> = cache
+			.packages(&PackageSort::default().installed().names())
+			.map(|pkg| pkg.fullname(true))
+			.collect();
+		let cand = pkg.candidate().unwrap().version().to_string();
+
+		let path = std::env::temp_dir().join("oma-apt-scenario");
+		cache.dump_scenario(&path).unwrap();
+
+		let scenario = Cache::from_scenario(&path).unwrap();
+		fs::remove_file(&path).unwrap();
+
+		let loaded: Vec<String> = scenario
+			.packages(&PackageSort::default().installed().names())
+			.map(|pkg| pkg.fullname(true))
+			.collect();
+		assert_eq!(loaded, installed);
+
+		let pkg = scenario.get("dep-pkg1").unwrap();
+		assert!(pkg.marked_install());
+		assert_eq!(pkg.candidate().unwrap().version(), cand);
+
+		assert!(Cache::from_scenario("/nonexistent/scenario").is_err());
+
+		// Nothing of the scenario is left for the next cache.
+		let config = Config::new();
+		let system = config.find("APT::System", "");
+		assert_ne!(system, "Debian APT solver interface");
+		assert!(config.get("edsp::scenario").is_none());
+		assert!(!config.bool("APT::Get::Simulate", false));
+		let cache = new_cache!().unwrap();
+		assert!(cache.get("apt").unwrap().is_installed());
+	}
+}
@@ -0,0 +1,57 @@
+mod search {
+	use oma_apt::new_cache;
+	use oma_apt::search::{SearchIndex, SearchQuery};
+
+	#[test]
+	fn exact_name_first() {
+		let cache = new_cache!().unwrap();
+		let results = cache.search(&SearchQuery::new("apt")).unwrap();
+
+		// An exact name match should always be ranked first.
+		assert_eq!(results.first().unwrap().package.name(), "apt");
+	}
+
+	#[test]
+	fn all_terms_match() {
+		let cache = new_cache!().unwrap();
+		let results = cache.search(&SearchQuery::new("apt package")).unwrap();
+
+		assert!(!results.is_empty());
+		// Every term contributes to the score, so two terms is at least 20.
+		assert!(results.iter().all(|r| r.score >= 20));
+
+		// A term that can never match filters out everything.
+		let results = cache
+			.search(&SearchQuery::new("apt zzz-not-a-real-term-zzz"))
+			.unwrap();
+		assert!(results.is_empty());
+
+		// Bad regex should be an error and not a panic.
+		assert!(cache.search(&SearchQuery::new("apt(")).is_err());
+	}
+
+	#[test]
+	fn index() {
+		let cache = new_cache!().unwrap();
+		let index = SearchIndex::new(&cache);
+		assert!(!index.is_empty());
+
+		let query = SearchQuery::new("^apt").names_only();
+		let from_index: Vec<_> = index
+			.search(&query)
+			.unwrap()
+			.into_iter()
+			.map(|r| r.package.name().to_string())
+			.collect();
+
+		let from_cache: Vec<_> = cache
+			.search(&query)
+			.unwrap()
+			.into_iter()
+			.map(|r| r.package.name().to_string())
+			.collect();
+
+		assert_eq!(from_index, from_cache);
+		assert!(from_index.iter().all(|name| name.starts_with("apt")));
+	}
+}
@@ -48,4 +48,34 @@ mod tagfile {
 			"\n\tAll my homies know that tabs be superior.\n\t   Why not just use both?"
 		);
 	}
+
+	#[test]
+	fn write() {
+		let control =
+			"Package: pkg1\nVersion: 1.0.0\nMulti-Line: Wow\n  This is\nEmpty:\n  Next line\n";
+		let mut section = TagSection::new(control).unwrap();
+
+		assert_eq!(section.to_string(), control);
+		assert_eq!(
+			section.keys().collect::<Vec<_>>(),
+			["Package", "Version", "Multi-Line", "Empty"]
+		);
+
+		section.set("Version", "2.0.0");
+		section.set("Depends", "apt");
+		assert_eq!(section.remove("Multi-Line").unwrap(), "Wow\n  This is");
+		assert!(section.remove("Multi-Line").is_none());
+
+		assert_eq!(
+			section.to_string(),
+			"Package: pkg1\nVersion: 2.0.0\nEmpty:\n  Next line\nDepends: apt\n"
+		);
+		assert_eq!(
+			TagSection::new(&section.to_string())
+				.unwrap()
+				.get("Empty")
+				.unwrap(),
+			"\n  Next line"
+		);
+	}
 }
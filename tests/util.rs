@@ -12,4 +12,23 @@ mod util {
 		assert_eq!(Ordering::Equal, util::cmp_versions(ver1, ver1));
 		assert_eq!(Ordering::Greater, util::cmp_versions(ver2, ver1));
 	}
+
+	#[test]
+	fn hash_file() {
+		let path = std::env::temp_dir().join("oma-apt-hash-file");
+		std::fs::write(&path, "hello").unwrap();
+
+		assert_eq!(
+			util::hash_file(&path, "MD5Sum").unwrap(),
+			"5d41402abc4b2a76b9719d911017c592"
+		);
+		assert_eq!(
+			util::hash_file(&path, "SHA256").unwrap(),
+			"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+		);
+		assert!(util::hash_file(&path, "not-a-hash").is_err());
+
+		std::fs::remove_file(&path).unwrap();
+		assert!(util::hash_file(&path, "MD5Sum").is_err());
+	}
 }
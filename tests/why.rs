@@ -0,0 +1,80 @@
+mod common;
+
+mod why {
+	use oma_apt::why::{Strength, WhyNot};
+	use oma_apt::{new_cache, DepType};
+
+	use crate::common::fixture_cache;
+
+	#[test]
+	fn why() {
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("libapt-pkg6.0").unwrap();
+
+		let chains = pkg.why();
+		// If libapt-pkg is auto installed, something must be holding it
+		if pkg.is_auto_installed() {
+			assert!(!chains.is_empty());
+		}
+
+		for chain in &chains {
+			// The root is always manually installed
+			assert!(!cache.get(chain.root()).unwrap().is_auto_installed());
+			println!("{chain}");
+		}
+
+		// Stronger chains must come first
+		for pair in chains.windows(2) {
+			assert!(pair[0].strength <= pair[1].strength);
+		}
+	}
+
+	#[test]
+	fn why_manual() {
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("apt").unwrap();
+
+		// Nothing can depend on a package that isn't held by anything
+		for chain in pkg.why() {
+			assert_ne!(chain.root(), "apt");
+			if chain.strength == Strength::Depends {
+				let links = &chain.links;
+				assert!(links.iter().all(|l| l.dep_type != DepType::Recommends));
+			}
+		}
+	}
+
+	#[test]
+	fn why_not() {
+		// wn-blocker conflicts with wn-target, and wn-target breaks wn-old.
+		let cache = fixture_cache("why-not");
+
+		let why_not = cache.get("wn-target").unwrap().why_not();
+		assert_eq!(
+			why_not,
+			[
+				WhyNot {
+					package: "wn-blocker".to_string(),
+					version: "1.0".to_string(),
+					dep_type: DepType::Conflicts,
+					relation: "wn-target".to_string(),
+					declared_by: "wn-blocker".to_string(),
+				},
+				WhyNot {
+					package: "wn-old".to_string(),
+					version: "1.0".to_string(),
+					dep_type: DepType::DpkgBreaks,
+					relation: "wn-old (<< 2.0)".to_string(),
+					declared_by: "wn-target".to_string(),
+				},
+			]
+		);
+		assert_eq!(why_not[0].to_string(), "wn-blocker Conflicts wn-target");
+
+		// apt is installed so nothing installed should conflict with it
+		let cache = new_cache!().unwrap();
+		for why_not in cache.get("apt").unwrap().why_not() {
+			assert_ne!(why_not.package, "apt");
+		}
+	}
+}